@@ -0,0 +1,161 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Index, Member, Path};
+
+/// How a field of a varying struct is blended between vertices.
+enum Qualifier {
+    /// Perspective-correct, the default.
+    Smooth,
+    /// Linear in screen space.
+    NoPerspective,
+    /// Taken from the provoking vertex, not interpolated.
+    Flat,
+}
+
+/// Derives `VertexShaderOutputPositionAndLerp` for a struct of varyings.
+///
+/// One field is marked `#[position]` and holds the clip space position, it is blended
+/// linearly in screen space. Every other field implements `Lerp` unless it is `#[flat]`,
+/// `#[smooth]` (the default) and `#[noperspective]` pick how it is interpolated. Flat
+/// fields take the value of the provoking vertex of a primitive.
+///
+/// The generated impl names the renderer traits as `crate::lps::...`, which holds inside the
+/// renderer crate. From elsewhere, `#[varyings(crate = path)]` on the struct gives the path
+/// the `lps` module is reached through.
+#[proc_macro_derive(Varyings, attributes(position, flat, noperspective, smooth, varyings))]
+pub fn derive_varyings(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "Varyings can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut krate: Path = syn::parse_quote!(crate);
+    for attr in &input.attrs {
+        if attr.path().is_ident("varyings") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    krate = meta.value()?.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected #[varyings(crate = path)]"))
+                }
+            })?;
+        }
+    }
+
+    let mut position = None;
+    let mut lerped = Vec::new();
+    let mut flat = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+
+        let mut is_position = false;
+        let mut qualifier = None;
+        for attr in &field.attrs {
+            let path = attr.path();
+            let found = if path.is_ident("position") {
+                is_position = true;
+                continue;
+            } else if path.is_ident("smooth") {
+                Qualifier::Smooth
+            } else if path.is_ident("noperspective") {
+                Qualifier::NoPerspective
+            } else if path.is_ident("flat") {
+                Qualifier::Flat
+            } else {
+                continue;
+            };
+            attr.meta.require_path_only()?;
+            if qualifier.replace(found).is_some() {
+                return Err(Error::new(
+                    attr.span(),
+                    "a varying takes one of #[flat], #[noperspective] and #[smooth]",
+                ));
+            }
+        }
+
+        if is_position {
+            if qualifier.is_some() {
+                return Err(Error::new(
+                    field.span(),
+                    "the #[position] field is always blended in screen space",
+                ));
+            }
+            if position.replace(member.clone()).is_some() {
+                return Err(Error::new(
+                    field.span(),
+                    "only one field can be #[position]",
+                ));
+            }
+        }
+
+        let value = match qualifier.unwrap_or(Qualifier::Smooth) {
+            Qualifier::Smooth if !is_position => {
+                quote!(Lerp::lerp(v1.#member, v2.#member, factor.perspective))
+            }
+            Qualifier::Smooth | Qualifier::NoPerspective => {
+                quote!(Lerp::lerp(v1.#member, v2.#member, factor.linear))
+            }
+            Qualifier::Flat => {
+                flat.push(quote!(self.#member = provoking.#member;));
+                quote!(v1.#member)
+            }
+        };
+        lerped.push(quote!(#member: #value));
+    }
+
+    let position = position
+        .ok_or_else(|| Error::new(input.span(), "Varyings needs a field marked #[position]"))?;
+
+    // without flat varyings the default of the trait does nothing
+    let copy_flat = (!flat.is_empty()).then(|| {
+        quote! {
+            fn copy_flat(&mut self, provoking: &Self) {
+                #(#flat)*
+            }
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::lps::rasterize::vt_output::VertexShaderOutputPositionAndLerp
+            for #name #ty_generics #where_clause
+        {
+            fn position_as_mut(&mut self) -> &mut #krate::lps::common::math::vec4::Vec4 {
+                &mut self.#position
+            }
+
+            fn position(&self) -> &#krate::lps::common::math::vec4::Vec4 {
+                &self.#position
+            }
+
+            fn lerp(
+                v1: &Self,
+                v2: &Self,
+                factor: #krate::lps::rasterize::vt_output::LerpFactor,
+            ) -> Self {
+                use #krate::lps::rasterize::vt_output::Lerp;
+                Self { #(#lerped,)* }
+            }
+
+            #copy_flat
+        }
+    })
+}
@@ -96,12 +96,23 @@ impl Mat4x4 {
         return mat.trans();
     }
 
-    pub fn viewport_mat(ox: i32, oy: i32, width: i32, height: i32) -> Mat4x4 {
+    /// Maps normalized device coordinates to render target pixels, x and y from -1..1 to
+    /// the viewport rectangle with y pointing down, z from -1..1 to min_depth..max_depth.
+    pub fn viewport_mat(
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        min_depth: f32,
+        max_depth: f32,
+    ) -> Mat4x4 {
         let mut mat = Mat4x4::identity();
-        mat[0][0] = width as f32 / 2.0;
-        mat[3][0] = ox as f32 + width as f32 / 2.0;
-        mat[1][1] = height as f32 / 2.0;
-        mat[3][1] = oy as f32 + height as f32 / 2.0;
+        mat[0][0] = width / 2.0;
+        mat[0][3] = x + width / 2.0;
+        mat[1][1] = -height / 2.0;
+        mat[1][3] = y + height / 2.0;
+        mat[2][2] = (max_depth - min_depth) / 2.0;
+        mat[2][3] = (max_depth + min_depth) / 2.0;
         return mat;
     }
 
@@ -134,8 +145,8 @@ impl Mat4x4 {
         mat[0][0] = 1.0 / (aspect * tan_half_fov);
         mat[1][1] = 1.0 / tan_half_fov;
         mat[2][2] = -(far + near) / (far - near);
-        mat[2][3] = -1.0;
-        mat[3][2] = -2.0 * far * near / (far - near);
+        mat[2][3] = -2.0 * far * near / (far - near);
+        mat[3][2] = -1.0;
         return mat;
     }
 
@@ -62,4 +62,32 @@ mod tests {
 
         assert!(res == right);
     }
+
+    #[test]
+    fn test_viewport_mat() {
+        let mat = Mat4x4::viewport_mat(10.0, 20.0, 200.0, 100.0, 0.25, 0.75);
+
+        // the top left corner of ndc maps to the viewport origin, y points down
+        let top_left = mat * Vec4::new(-1.0, 1.0, -1.0, 1.0);
+        assert!(top_left == Vec4::new(10.0, 20.0, 0.25, 1.0));
+        let bottom_right = mat * Vec4::new(1.0, -1.0, 1.0, 1.0);
+        assert!(bottom_right == Vec4::new(210.0, 120.0, 0.75, 1.0));
+    }
+
+    #[test]
+    fn test_perspective_mat() {
+        let mat = Mat4x4::perspective_mat(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 100.0);
+
+        // the camera looks down -z, a point between near and far lands inside the clip volume
+        for depth in [1.5, 10.0, 99.0] {
+            let clip = mat * Vec4::new(0.5, -0.5, -depth, 1.0);
+            assert!(clip.w > 0.0);
+            let z = clip.z / clip.w;
+            assert!((-1.0..=1.0).contains(&z), "z {} at depth {}", z, depth);
+        }
+        let near = mat * Vec4::new(0.0, 0.0, -1.0, 1.0);
+        assert!((near.z / near.w + 1.0).abs() < 1e-5);
+        let far = mat * Vec4::new(0.0, 0.0, -100.0, 1.0);
+        assert!((far.z / far.w - 1.0).abs() < 1e-5);
+    }
 }
@@ -1,4 +1,4 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Vec2 {
@@ -22,6 +22,10 @@ impl Vec2 {
         Vec2::new(self.x + other.x, self.y + other.y)
     }
 
+    pub fn do_sub(&self, other: &Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+
     pub fn do_add_scalar(&self, n: f32) -> Vec2 {
         Vec2::new(self.x + n, self.y + n)
     }
@@ -96,6 +100,14 @@ impl Add for Vec2 {
     }
 }
 
+impl Sub for Vec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.do_sub(&rhs)
+    }
+}
+
 impl AddAssign for Vec2 {
     fn add_assign(&mut self, rhs: Self) {
         *self = self.do_add(&rhs);
@@ -1,4 +1,4 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Vec3 {
@@ -23,6 +23,10 @@ impl Vec3 {
         Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
     }
 
+    pub fn do_sub(&self, other: &Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+
     pub fn do_add_scalar(&self, n: f32) -> Vec3 {
         Vec3::new(self.x + n, self.y + n, self.z + n)
     }
@@ -97,6 +101,14 @@ impl Add for Vec3 {
     }
 }
 
+impl Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.do_sub(&rhs)
+    }
+}
+
 impl AddAssign for Vec3 {
     fn add_assign(&mut self, rhs: Self) {
         *self = self.do_add(&rhs);
@@ -1,4 +1,4 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Vec4 {
@@ -29,6 +29,15 @@ impl Vec4 {
         )
     }
 
+    pub fn do_sub(&self, other: &Vec4) -> Vec4 {
+        Vec4::new(
+            self.x - other.x,
+            self.y - other.y,
+            self.z - other.z,
+            self.w - other.w,
+        )
+    }
+
     pub fn do_add_scalar(&self, n: f32) -> Vec4 {
         Vec4::new(self.x + n, self.y + n, self.z + n, self.w + n)
     }
@@ -103,6 +112,14 @@ impl Add for Vec4 {
     }
 }
 
+impl Sub for Vec4 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.do_sub(&rhs)
+    }
+}
+
 impl AddAssign for Vec4 {
     fn add_assign(&mut self, rhs: Self) {
         *self = self.do_add(&rhs);
@@ -1,9 +1,10 @@
-use std::any::Any;
+use crate::lps::rasterize::buffer::{BufferData, IndexBuffer};
 use std::sync::Arc;
 
+/// Copies of the mesh data to upload into Gpu buffers.
 pub trait MeshShared {
-    fn vertex_list(&self) -> Vec<Arc<dyn Any + Send + Sync>>;
-    fn index_list(&self) -> Vec<usize>;
+    fn vertex_data(&self) -> BufferData;
+    fn index_data(&self) -> IndexBuffer;
 }
 
 pub struct Mesh<Vertex>
@@ -11,19 +12,19 @@ where
     Vertex: Clone + Send + Sync,
 {
     vertices: Vec<Vertex>,
-    indices: Vec<usize>,
+    indices: Vec<u32>,
 }
 
 impl<Vertex> Mesh<Vertex>
 where
     Vertex: Clone + Send + Sync,
 {
-    pub fn new_with_data(vertices: Vec<Vertex>, indices: Vec<usize>) -> Self {
+    pub fn new_with_data(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
         Self { vertices, indices }
     }
 
     pub fn add_triangle(&mut self, v0: Vertex, v1: Vertex, v2: Vertex) {
-        let idx = self.vertices.len() as usize;
+        let idx = self.vertices.len() as u32;
         self.vertices.push(v0);
         self.vertices.push(v1);
         self.vertices.push(v2);
@@ -33,7 +34,7 @@ where
     }
 
     pub fn add_mesh(&mut self, mesh: &Mesh<Vertex>) {
-        let idx = self.vertices.len() as usize;
+        let idx = self.vertices.len() as u32;
         for vertex in &mesh.vertices {
             self.vertices.push(vertex.clone());
         }
@@ -47,14 +48,11 @@ impl<Vertex> MeshShared for Mesh<Vertex>
 where
     Vertex: Clone + Send + Sync + 'static,
 {
-    fn vertex_list(&self) -> Vec<Arc<dyn Any + Send + Sync>> {
-        self.vertices
-            .iter()
-            .map(|vertex| Arc::new(vertex.clone()) as Arc<dyn Any + Send + Sync>)
-            .collect::<Vec<Arc<dyn Any + Send + Sync>>>()
+    fn vertex_data(&self) -> BufferData {
+        BufferData::vertex(Arc::from(self.vertices.as_slice()))
     }
 
-    fn index_list(&self) -> Vec<usize> {
-        self.indices.clone()
+    fn index_data(&self) -> IndexBuffer {
+        IndexBuffer::new_compact(&self.indices)
     }
 }
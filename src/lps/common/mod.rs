@@ -1,5 +1,8 @@
 pub mod color;
 pub mod math;
 pub mod mesh;
+pub mod rect;
 pub mod render_window;
+pub mod sampler;
 pub mod texture;
+pub mod texture_unittests;
@@ -0,0 +1,38 @@
+/// Axis aligned rectangle in render target pixels, (x, y) is the top left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub const fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Exclusive right bound.
+    pub fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    /// Exclusive bottom bound.
+    pub fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    /// The overlapping part of both rectangles, empty (zero sized) if they do not overlap.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right()).max(x);
+        let bottom = self.bottom().min(other.bottom()).max(y);
+        Rect::new(x, y, right - x, bottom - y)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::lps::common::color::Color;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterMode {
+    /// Nearest texel of the base level.
+    Nearest,
+    /// Blend of the four nearest texels of the base level.
+    Bilinear,
+    /// Bilinear samples of the two closest mip levels, blended by the level of detail.
+    Trilinear,
+}
+
+/// How texel coordinates outside of the texture are resolved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AddressMode {
+    /// Repeats the texture.
+    Wrap,
+    /// Repeats the edge texels.
+    Clamp,
+    /// Repeats the texture, flipping every other copy.
+    Mirror,
+    /// Returns the border color.
+    Border,
+}
+
+impl AddressMode {
+    /// Maps a texel coordinate onto `0..size`, or `None` for the border.
+    pub fn resolve(&self, coord: i64, size: u32) -> Option<u32> {
+        let size = size as i64;
+        let coord = match self {
+            AddressMode::Wrap => coord.rem_euclid(size),
+            AddressMode::Clamp => coord.clamp(0, size - 1),
+            AddressMode::Mirror => {
+                let coord = coord.rem_euclid(size * 2);
+                if coord < size {
+                    coord
+                } else {
+                    size * 2 - 1 - coord
+                }
+            }
+            AddressMode::Border => {
+                if coord < 0 || coord >= size {
+                    return None;
+                }
+                coord
+            }
+        };
+        Some(coord as u32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sampler {
+    pub address_u: AddressMode,
+    pub address_v: AddressMode,
+    pub border_color: Color,
+    pub filter_mode: FilterMode,
+}
+
+impl Sampler {
+    pub fn new(address_mode: AddressMode, filter_mode: FilterMode) -> Self {
+        Self {
+            address_u: address_mode,
+            address_v: address_mode,
+            border_color: Color::new_rgba(0, 0, 0, 0),
+            filter_mode,
+        }
+    }
+
+    pub fn with_border_color(mut self, border_color: Color) -> Self {
+        self.border_color = border_color;
+        self
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler::new(AddressMode::Wrap, FilterMode::Nearest)
+    }
+}
@@ -1,14 +1,114 @@
 use crate::lps::common::color::Color;
 use crate::lps::common::math::vec2::Vec2;
+use crate::lps::common::math::vec4::Vec4;
+use crate::lps::common::sampler::{FilterMode, Sampler};
 
 #[derive(Clone)]
-pub struct Texture {
+struct MipLevel {
     width: u32,
     height: u32,
     data: Vec<Color>,
 }
 
+impl MipLevel {
+    fn texel(&self, sampler: &Sampler, x: i64, y: i64) -> Vec4 {
+        let x = sampler.address_u.resolve(x, self.width);
+        let y = sampler.address_v.resolve(y, self.height);
+        let color = match (x, y) {
+            (Some(x), Some(y)) => &self.data[(y * self.width + x) as usize],
+            _ => &sampler.border_color,
+        };
+        Texture::color_to_vec4(color)
+    }
+
+    fn sample_nearest(&self, sampler: &Sampler, texcoord: Vec2) -> Vec4 {
+        let x = (texcoord.x * self.width as f32).floor() as i64;
+        let y = (texcoord.y * self.height as f32).floor() as i64;
+        self.texel(sampler, x, y)
+    }
+
+    fn sample_bilinear(&self, sampler: &Sampler, texcoord: Vec2) -> Vec4 {
+        // texel centers are at half-integer coordinates
+        let x = texcoord.x * self.width as f32 - 0.5;
+        let y = texcoord.y * self.height as f32 - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let c00 = self.texel(sampler, x0, y0);
+        let c10 = self.texel(sampler, x0 + 1, y0);
+        let c01 = self.texel(sampler, x0, y0 + 1);
+        let c11 = self.texel(sampler, x0 + 1, y0 + 1);
+
+        Vec4::lerp(Vec4::lerp(c00, c10, fx), Vec4::lerp(c01, c11, fx), fy)
+    }
+
+    // box filters 2x2 texels into one, odd sizes repeat their last row or column
+    fn down_sample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+
+        let mut data = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let x0 = (x * 2).min(self.width - 1);
+                let x1 = (x * 2 + 1).min(self.width - 1);
+                let y0 = (y * 2).min(self.height - 1);
+                let y1 = (y * 2 + 1).min(self.height - 1);
+
+                let sum = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+                    .iter()
+                    .map(|&(x, y)| {
+                        Texture::color_to_vec4(&self.data[(y * self.width + x) as usize])
+                    })
+                    .fold(Vec4::ZERO, |sum, color| sum + color);
+                data.push(Texture::vec4_to_color(&(sum / 4.0)));
+            }
+        }
+
+        MipLevel {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    mip_levels: Vec<MipLevel>,
+}
+
 impl Texture {
+    pub fn new_with_data(width: u32, height: u32, data: Vec<Color>) -> Texture {
+        assert_eq!(data.len(), (width * height) as usize);
+
+        // level 0 is the image itself, every further level halves the size down to 1x1
+        let mut mip_levels = vec![MipLevel {
+            width,
+            height,
+            data,
+        }];
+        loop {
+            let last = mip_levels.last().unwrap();
+            if last.width == 1 && last.height == 1 {
+                break;
+            }
+            let next = last.down_sample();
+            mip_levels.push(next);
+        }
+
+        Texture {
+            width,
+            height,
+            mip_levels,
+        }
+    }
+
     pub fn load(path: &str) -> Texture {
         let img = image::open(path).unwrap();
         let img_raw_data = img.as_bytes();
@@ -28,21 +128,75 @@ impl Texture {
             }
         }
 
-        Texture {
-            width: img.width(),
-            height: img.height(),
-            data,
+        Texture::new_with_data(w, h, data)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn mip_level_cnt(&self) -> usize {
+        self.mip_levels.len()
+    }
+
+    /// Samples the base level.
+    pub fn sample(&self, sampler: &Sampler, texcoord: Vec2) -> Color {
+        Texture::vec4_to_color(&self.sample_level(sampler, texcoord, 0.0))
+    }
+
+    /// Samples with the screen space derivatives of `texcoord`, which select the mip level.
+    pub fn sample_grad(&self, sampler: &Sampler, texcoord: Vec2, ddx: Vec2, ddy: Vec2) -> Color {
+        let lod = self.level_of_detail(ddx, ddy);
+        Texture::vec4_to_color(&self.sample_level(sampler, texcoord, lod))
+    }
+
+    /// Level of detail for the given texcoord derivatives, clamped to the mip chain.
+    pub fn level_of_detail(&self, ddx: Vec2, ddy: Vec2) -> f32 {
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        let ddx = Vec2::new(ddx.x * size.x, ddx.y * size.y);
+        let ddy = Vec2::new(ddy.x * size.x, ddy.y * size.y);
+        let rho = ddx.len().max(ddy.len());
+        if rho <= 1.0 {
+            return 0.0;
         }
+        rho.log2().min((self.mip_levels.len() - 1) as f32)
     }
 
-    pub fn sample2d(&self, texcoord: Vec2) -> Color {
-        let x = texcoord.x - texcoord.x.floor();
-        let y = texcoord.y - texcoord.y.floor();
+    fn sample_level(&self, sampler: &Sampler, texcoord: Vec2, lod: f32) -> Vec4 {
+        match sampler.filter_mode {
+            FilterMode::Nearest => self.mip_levels[0].sample_nearest(sampler, texcoord),
+            FilterMode::Bilinear => self.mip_levels[0].sample_bilinear(sampler, texcoord),
+            FilterMode::Trilinear => {
+                let level = lod.floor() as usize;
+                let color = self.mip_levels[level].sample_bilinear(sampler, texcoord);
+                if level + 1 >= self.mip_levels.len() {
+                    return color;
+                }
+                let next_color = self.mip_levels[level + 1].sample_bilinear(sampler, texcoord);
+                Vec4::lerp(color, next_color, lod - level as f32)
+            }
+        }
+    }
 
-        let color_x = (x * (self.width - 1) as f32) as u32;
-        let color_y = (y * (self.height - 1) as f32) as u32;
+    fn color_to_vec4(color: &Color) -> Vec4 {
+        Vec4::new(
+            color.r as f32,
+            color.g as f32,
+            color.b as f32,
+            color.a as f32,
+        )
+    }
 
-        // println!("color: {:?}, {:?}", x, y);
-        return self.data[(color_y * self.width + color_x) as usize];
+    fn vec4_to_color(color: &Vec4) -> Color {
+        Color::new_rgba(
+            color.x.round() as u8,
+            color.y.round() as u8,
+            color.z.round() as u8,
+            color.w.round() as u8,
+        )
     }
 }
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::color::Color;
+    use crate::lps::common::math::vec2::Vec2;
+    use crate::lps::common::sampler::{AddressMode, FilterMode, Sampler};
+    use crate::lps::common::texture::Texture;
+
+    // black and white texels alternating like a chess board
+    fn create_checker_texture(width: u32, height: u32) -> Texture {
+        let mut data = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                let value = if (x + y) % 2 == 0 { 255 } else { 0 };
+                data.push(Color::new_rgba(value, value, value, 255));
+            }
+        }
+        Texture::new_with_data(width, height, data)
+    }
+
+    #[test]
+    fn test_mip_chain() {
+        let texture = create_checker_texture(64, 32);
+        assert_eq!(texture.mip_level_cnt(), 7);
+
+        let texel = Vec2::new(1.0 / 64.0, 0.0);
+        assert_eq!(texture.level_of_detail(texel, Vec2::ZERO), 0.0);
+        assert_eq!(texture.level_of_detail(texel * 4.0, Vec2::ZERO), 2.0);
+        assert_eq!(texture.level_of_detail(texel * 1000.0, Vec2::ZERO), 6.0);
+    }
+
+    #[test]
+    fn test_filter_modes() {
+        let texture = create_checker_texture(64, 64);
+        let texcoord = Vec2::new(0.3, 0.7);
+        let minified = Vec2::new(8.0 / 64.0, 0.0);
+
+        // point sampling keeps aliasing, the mip chain averages the checker to gray
+        let sampler = Sampler::new(AddressMode::Wrap, FilterMode::Nearest);
+        let color = texture.sample_grad(&sampler, texcoord, minified, minified);
+        assert!(color.r == 0 || color.r == 255);
+
+        let sampler = Sampler::new(AddressMode::Wrap, FilterMode::Trilinear);
+        let color = texture.sample_grad(&sampler, texcoord, minified, minified);
+        assert!((color.r as i32 - 128).abs() <= 1, "{:?}", color);
+
+        // halfway between two texel centers on a row, bilinear blends them evenly
+        let sampler = Sampler::new(AddressMode::Wrap, FilterMode::Bilinear);
+        let color = texture.sample(&sampler, Vec2::new(1.0 / 64.0, 0.5 / 64.0));
+        assert!((color.r as i32 - 128).abs() <= 1, "{:?}", color);
+    }
+
+    #[test]
+    fn test_address_modes() {
+        // a 4x1 gradient, texel x has the value 10 * x
+        let data = (0..4).map(|x| Color::new_rgba(x * 10, 0, 0, 255)).collect();
+        let texture = Texture::new_with_data(4, 1, data);
+        let red_at = |sampler: &Sampler, u: f32| texture.sample(sampler, Vec2::new(u, 0.5)).r;
+
+        let wrap = Sampler::new(AddressMode::Wrap, FilterMode::Nearest);
+        assert_eq!(red_at(&wrap, 1.125), 0);
+        assert_eq!(red_at(&wrap, -0.125), 30);
+
+        let clamp = Sampler::new(AddressMode::Clamp, FilterMode::Nearest);
+        assert_eq!(red_at(&clamp, 1.625), 30);
+        assert_eq!(red_at(&clamp, -3.0), 0);
+
+        let mirror = Sampler::new(AddressMode::Mirror, FilterMode::Nearest);
+        assert_eq!(red_at(&mirror, 1.125), 30);
+        assert_eq!(red_at(&mirror, -0.125), 0);
+        assert_eq!(red_at(&mirror, -0.375), 10);
+        assert_eq!(red_at(&mirror, 2.125), 0);
+
+        let border = Sampler::new(AddressMode::Border, FilterMode::Nearest)
+            .with_border_color(Color::new_rgba(99, 0, 0, 0));
+        assert_eq!(red_at(&border, 0.875), 30);
+        assert_eq!(red_at(&border, 1.125), 99);
+        assert_eq!(red_at(&border, -0.125), 99);
+
+        // clamping keeps bilinear filtering from bleeding in the opposite edge
+        let wrap = Sampler::new(AddressMode::Wrap, FilterMode::Bilinear);
+        let clamp = Sampler::new(AddressMode::Clamp, FilterMode::Bilinear);
+        assert_eq!(red_at(&wrap, 1.0), 15);
+        assert_eq!(red_at(&clamp, 1.0), 30);
+    }
+}
@@ -1,17 +1,52 @@
-use crate::lps::common::math::mat4x4::Mat4x4;
 use crate::lps::common::math::vec4::Vec4;
 use crate::lps::common::mesh::MeshShared;
+use crate::lps::common::rect::Rect;
 use crate::lps::common::texture::Texture;
-use crate::lps::rasterize::render_cmds::clear::ClearCmd;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::blend_state::BlendState;
+use crate::lps::rasterize::buffer::{BufferData, IndexBuffer, MeshBuffers};
+use crate::lps::rasterize::constant_buffer::ConstantBuffer;
+use crate::lps::rasterize::depth_stencil_state::DepthStencilState;
+use crate::lps::rasterize::pipeline_state::{AnyPipelineState, PipelineState, PipelineStateData};
+use crate::lps::rasterize::pixel_format::PixelFormat;
+use crate::lps::rasterize::primitive_topology::PrimitiveTopology;
+use crate::lps::rasterize::rasterizer_state::RasterizerState;
+use crate::lps::rasterize::render_cmds::clear::{ClearCmd, ClearFlags};
+use crate::lps::rasterize::render_cmds::create_buffer::CreateBufferCmd;
+use crate::lps::rasterize::render_cmds::create_pipeline_state::CreatePipelineStateCmd;
+use crate::lps::rasterize::render_cmds::create_render_target::CreateRenderTargetCmd;
+use crate::lps::rasterize::render_cmds::create_texture::CreateTextureCmd;
+use crate::lps::rasterize::render_cmds::destroy_buffer::DestroyBufferCmd;
+use crate::lps::rasterize::render_cmds::destroy_pipeline_state::DestroyPipelineStateCmd;
+use crate::lps::rasterize::render_cmds::destroy_render_target::DestroyRenderTargetCmd;
+use crate::lps::rasterize::render_cmds::destroy_texture::DestroyTextureCmd;
 use crate::lps::rasterize::render_cmds::draw::DrawCmd;
+use crate::lps::rasterize::render_cmds::read_render_target::ReadRenderTargetCmd;
 use crate::lps::rasterize::render_cmds::render_cmd::RenderCmd;
+use crate::lps::rasterize::render_cmds::resolve::ResolveCmd;
+use crate::lps::rasterize::render_cmds::set_attachment_blend_state::SetAttachmentBlendStateCmd;
+use crate::lps::rasterize::render_cmds::set_blend_state::SetBlendStateCmd;
 use crate::lps::rasterize::render_cmds::set_constant_buffer::SetConstantBufferCmd;
+use crate::lps::rasterize::render_cmds::set_depth_stencil_state::SetDepthStencilStateCmd;
 use crate::lps::rasterize::render_cmds::set_index_buffer::SetIndexBufferCmd;
+use crate::lps::rasterize::render_cmds::set_pipeline::SetPipelineCmd;
+use crate::lps::rasterize::render_cmds::set_primitive_topology::SetPrimitiveTopologyCmd;
+use crate::lps::rasterize::render_cmds::set_rasterizer_state::SetRasterizerStateCmd;
 use crate::lps::rasterize::render_cmds::set_render_target::SetRenderTargetCmd;
+use crate::lps::rasterize::render_cmds::set_render_targets::SetRenderTargetsCmd;
+use crate::lps::rasterize::render_cmds::set_scissor::SetScissorCmd;
+use crate::lps::rasterize::render_cmds::set_texture::SetTextureCmd;
 use crate::lps::rasterize::render_cmds::set_vertex_buffer::SetVertexBufferCmd;
+use crate::lps::rasterize::render_cmds::set_viewport::SetViewportCmd;
 use crate::lps::rasterize::render_cmds::swap::Swap;
+use crate::lps::rasterize::render_cmds::update_buffer::UpdateBufferCmd;
+use crate::lps::rasterize::render_cmds::update_texture::UpdateTextureCmd;
 use crate::lps::rasterize::render_target::RenderTarget;
-use std::any::Any;
+use crate::lps::rasterize::resource::{
+    BufferId, HandleAllocator, PipelineStateId, RenderTargetId, TextureId,
+};
+use crate::lps::rasterize::viewport::Viewport;
+use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
 use super::bus::{BusMutex, ExitNotifyCondVar, RenderCompleteNotifyCondVar};
@@ -25,6 +60,10 @@ pub struct Cpu<'a>
     render_cnt: i32,
     render_loop: fn(cpu: &mut Cpu, gpu_exit_mutex: Arc<Mutex<bool>>) -> (),
     gpu_exit_mutex: Arc<Mutex<bool>>,
+    buffers: HandleAllocator<BufferData>,
+    textures: HandleAllocator<Texture>,
+    render_targets: HandleAllocator<RenderTarget>,
+    pipeline_states: HandleAllocator<PipelineStateData>,
 }
 
 impl<'a> Cpu<'a> {
@@ -42,6 +81,10 @@ impl<'a> Cpu<'a> {
             render_cnt: 0,
             render_loop,
             gpu_exit_mutex,
+            buffers: HandleAllocator::new(),
+            textures: HandleAllocator::new(),
+            render_targets: HandleAllocator::new(),
+            pipeline_states: HandleAllocator::new(),
         }
     }
 
@@ -68,40 +111,212 @@ impl<'a> Cpu<'a> {
         self.render_cnt = *guard;
     }
 
-    pub fn bind_vertex_buffer(&mut self, vertex_buffer: Vec<Arc<dyn Any + Send + Sync>>) {
+    /// Queues the upload of a vertex buffer, it can be drawn with the pipeline states of
+    /// the vertex type `V`.
+    pub fn create_vertex_buffer<V: Send + Sync + 'static>(
+        &mut self,
+        vertices: Arc<[V]>,
+    ) -> BufferId {
+        self.create_buffer(BufferData::vertex(vertices))
+    }
+
+    pub fn create_index_buffer(&mut self, indices: IndexBuffer) -> BufferId {
+        self.create_buffer(BufferData::Index(indices))
+    }
+
+    fn create_buffer(&mut self, data: BufferData) -> BufferId {
+        let id = self.buffers.allocate();
+        self.add_cmd(CreateBufferCmd::new(id, data));
+        id
+    }
+
+    /// Queues new contents for a buffer, a vertex buffer stays a vertex buffer.
+    pub fn update_buffer(&mut self, id: BufferId, data: BufferData) -> Result<(), GpuError> {
+        self.buffers.check(id)?;
+        self.add_cmd(UpdateBufferCmd::new(id, data));
+        Ok(())
+    }
+
+    pub fn destroy_buffer(&mut self, id: BufferId) -> Result<(), GpuError> {
+        self.buffers.free(id)?;
+        self.add_cmd(DestroyBufferCmd::new(id));
+        Ok(())
+    }
+
+    pub fn create_mesh_buffers(&mut self, mesh: &dyn MeshShared) -> MeshBuffers {
+        MeshBuffers {
+            vertex_buffer: self.create_buffer(mesh.vertex_data()),
+            index_buffer: self.create_index_buffer(mesh.index_data()),
+        }
+    }
+
+    pub fn bind_vertex_buffer(&mut self, vertex_buffer: BufferId) {
         self.add_cmd(SetVertexBufferCmd::new(vertex_buffer));
     }
 
-    pub fn bind_index_buffer(&mut self, index_buffer: Vec<usize>) {
+    pub fn bind_index_buffer(&mut self, index_buffer: BufferId) {
         self.add_cmd(SetIndexBufferCmd::new(index_buffer));
     }
 
-    pub fn bind_mesh(&mut self, mesh: &dyn MeshShared) {
-        self.bind_vertex_buffer(mesh.vertex_list());
-        self.bind_index_buffer(mesh.index_list());
+    pub fn bind_mesh(&mut self, mesh: &MeshBuffers) {
+        self.bind_vertex_buffer(mesh.vertex_buffer);
+        self.bind_index_buffer(mesh.index_buffer);
     }
 
-    pub fn bind_render_target(&mut self, render_target: Arc<Mutex<RenderTarget>>) {
-        self.add_cmd(SetRenderTargetCmd::new(render_target));
+    pub fn create_texture(&mut self, texture: Texture) -> TextureId {
+        let id = self.textures.allocate();
+        self.add_cmd(CreateTextureCmd::new(id, Arc::new(texture)));
+        id
     }
 
-    pub fn bind_constant_buffer_mat4x4(&mut self, index: usize, mat: Mat4x4) {
-        self.add_cmd(SetConstantBufferCmd::new_with_mat4x4(index, mat));
+    pub fn update_texture(&mut self, id: TextureId, texture: Texture) -> Result<(), GpuError> {
+        self.textures.check(id)?;
+        self.add_cmd(UpdateTextureCmd::new(id, Arc::new(texture)));
+        Ok(())
     }
 
-    pub fn bind_constant_buffer_vec4(&mut self, index: usize, vec: Vec4) {
-        self.add_cmd(SetConstantBufferCmd::new_with_vec4(index, vec));
+    pub fn destroy_texture(&mut self, id: TextureId) -> Result<(), GpuError> {
+        self.textures.free(id)?;
+        self.add_cmd(DestroyTextureCmd::new(id));
+        Ok(())
     }
 
-    pub fn bind_constant_buffer_texture(&mut self, index: usize, texture: Arc<Mutex<Texture>>) {
-        self.add_cmd(SetConstantBufferCmd::new_with_texture(
-            index,
-            Arc::clone(&texture),
+    pub fn create_render_target(&mut self, width: u32, height: u32) -> RenderTargetId {
+        self.create_multisampled_render_target(width, height, 1)
+    }
+
+    pub fn create_multisampled_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        sample_cnt: u32,
+    ) -> RenderTargetId {
+        self.create_render_target_with_format(width, height, sample_cnt, PixelFormat::Rgba8)
+    }
+
+    /// Creates a render target storing its colors in `format`, a float format keeps
+    /// colors above 255 for HDR lighting.
+    pub fn create_render_target_with_format(
+        &mut self,
+        width: u32,
+        height: u32,
+        sample_cnt: u32,
+        format: PixelFormat,
+    ) -> RenderTargetId {
+        let id = self.render_targets.allocate();
+        self.add_cmd(CreateRenderTargetCmd::new(
+            id, width, height, sample_cnt, format,
+        ));
+        id
+    }
+
+    pub fn destroy_render_target(&mut self, id: RenderTargetId) -> Result<(), GpuError> {
+        self.render_targets.free(id)?;
+        self.add_cmd(DestroyRenderTargetCmd::new(id));
+        Ok(())
+    }
+
+    /// Waits for the Gpu to reach this command and returns a copy of the render target.
+    pub fn read_render_target(&mut self, id: RenderTargetId) -> Result<RenderTarget, GpuError> {
+        self.render_targets.check(id)?;
+        let (sender, receiver) = mpsc::channel();
+        self.add_cmd(ReadRenderTargetCmd::new(id, sender));
+        receiver
+            .recv()
+            .expect("the gpu stopped before reading the render target")
+    }
+
+    /// Queues the creation of a pipeline state, draws with it read vertex buffers of its
+    /// vertex input type.
+    pub fn create_pipeline_state<VSInput, VSOutput>(
+        &mut self,
+        pipeline_state: PipelineState<VSInput, VSOutput>,
+    ) -> PipelineStateId
+    where
+        PipelineState<VSInput, VSOutput>: AnyPipelineState + 'static,
+    {
+        let id = self.pipeline_states.allocate();
+        self.add_cmd(CreatePipelineStateCmd::new(
+            id,
+            PipelineStateData::new(pipeline_state),
         ));
+        id
+    }
+
+    pub fn destroy_pipeline_state(&mut self, id: PipelineStateId) -> Result<(), GpuError> {
+        self.pipeline_states.free(id)?;
+        self.add_cmd(DestroyPipelineStateCmd::new(id));
+        Ok(())
+    }
+
+    /// Binds the shaders and the fixed-function state of a pipeline state, draws after it
+    /// use them until another one is bound.
+    pub fn bind_pipeline(&mut self, pipeline_state: PipelineStateId) {
+        self.add_cmd(SetPipelineCmd::new(pipeline_state));
+    }
+
+    /// Binds `render_target` as the only color attachment and the depth attachment.
+    pub fn bind_render_target(&mut self, render_target: RenderTargetId) {
+        self.add_cmd(SetRenderTargetCmd::new(render_target));
+    }
+
+    /// Binds up to `MAX_COLOR_ATTACHMENTS` color attachments and a depth attachment, all of
+    /// the same size and sample count.
+    pub fn bind_render_targets(
+        &mut self,
+        color_attachments: &[RenderTargetId],
+        depth_attachment: Option<RenderTargetId>,
+    ) {
+        self.add_cmd(SetRenderTargetsCmd::new(
+            color_attachments.to_vec(),
+            depth_attachment,
+        ));
+    }
+
+    /// Binds `buffer` to the constant buffer slot `index`, the Gpu checks it against the
+    /// layout the shaders declare.
+    pub fn bind_constant_buffer<T: ConstantBuffer>(&mut self, index: usize, buffer: T) {
+        self.add_cmd(SetConstantBufferCmd::new(index, buffer));
+    }
+
+    pub fn bind_texture(&mut self, index: usize, texture: TextureId) {
+        self.add_cmd(SetTextureCmd::new(index, texture));
+    }
+
+    pub fn set_rasterizer_state(&mut self, rasterizer_state: RasterizerState) {
+        self.add_cmd(SetRasterizerStateCmd::new(rasterizer_state));
+    }
+
+    pub fn set_depth_stencil_state(&mut self, depth_stencil_state: DepthStencilState) {
+        self.add_cmd(SetDepthStencilStateCmd::new(depth_stencil_state));
+    }
+
+    pub fn set_blend_state(&mut self, blend_state: BlendState) {
+        self.add_cmd(SetBlendStateCmd::new(blend_state));
+    }
+
+    pub fn set_attachment_blend_state(&mut self, attachment: usize, blend_state: BlendState) {
+        self.add_cmd(SetAttachmentBlendStateCmd::new(attachment, blend_state));
+    }
+
+    pub fn set_primitive_topology(&mut self, primitive_topology: PrimitiveTopology) {
+        self.add_cmd(SetPrimitiveTopologyCmd::new(primitive_topology));
+    }
+
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.add_cmd(SetViewportCmd::new(viewport));
+    }
+
+    pub fn set_scissor(&mut self, scissor: Option<Rect>) {
+        self.add_cmd(SetScissorCmd::new(scissor));
+    }
+
+    pub fn clear(&mut self, flags: ClearFlags, color: Vec4, depth: f32, stencil: u8) {
+        self.add_cmd(ClearCmd::new(flags, color, depth, stencil));
     }
 
-    pub fn clear(&mut self, color: Vec4) {
-        self.add_cmd(ClearCmd::new(color));
+    pub fn resolve(&mut self, dst: RenderTargetId) {
+        self.add_cmd(ResolveCmd::new(dst));
     }
 
     pub fn draw(&mut self, with_index: bool) {
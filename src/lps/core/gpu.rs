@@ -2,243 +2,576 @@ use super::{
     bus::{BusMutex, ExitNotifyCondVar},
     unit::Unit,
 };
-use crate::lps::common::color::Color;
-use crate::lps::common::math::mat4x4::Mat4x4;
 use crate::lps::common::math::vec4::Vec4;
+use crate::lps::common::rect::Rect;
+use crate::lps::common::texture::Texture;
 use crate::lps::core::bus::RenderCompleteNotifyCondVar;
-use crate::lps::rasterize::pipeline::{PipeLine, PixelShader, VertexShader};
-use crate::lps::rasterize::render_target::RenderTarget;
-use crate::lps::rasterize::render_util::RenderUtil;
-use crate::lps::rasterize::vt_output::VertexShaderOutputPositionAndLerp;
-use std::fmt::Debug;
-use std::ops::DerefMut;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::blend_state::BlendState;
+use crate::lps::rasterize::buffer::BufferData;
+use crate::lps::rasterize::constant_buffer::ConstantBuffers;
+use crate::lps::rasterize::depth_stencil_state::DepthStencilState;
+use crate::lps::rasterize::draw_call::DrawCall;
+use crate::lps::rasterize::pipeline::PipeLine;
+use crate::lps::rasterize::pipeline_state::{AnyPipelineState, PipelineStateData};
+use crate::lps::rasterize::pixel_format::PixelFormat;
+use crate::lps::rasterize::primitive_topology::PrimitiveTopology;
+use crate::lps::rasterize::rasterizer_state::RasterizerState;
+use crate::lps::rasterize::render_cmds::clear::ClearFlags;
+use crate::lps::rasterize::render_target::{
+    Attachments, RenderTarget, MAX_COLOR_ATTACHMENTS, MAX_RENDER_TARGET_SAMPLES,
+};
+use crate::lps::rasterize::resource::{
+    BufferId, PipelineStateId, RenderTargetId, ResourceTable, TextureId,
+};
+use crate::lps::rasterize::viewport::Viewport;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use std::{any::Any, sync::Mutex};
 
+const CONSTANT_BUFFER_SLOT_CNT: usize = 32;
+
+/// Commands on handles return an error instead of panicking when the handle is stale or
+/// names a resource of the wrong kind, the Gpu reports it and goes on with the next command.
 pub trait GpuApi<'a> {
-    fn set_vertex_buffer(&mut self, vertex_list: Vec<Arc<dyn Any + Send + Sync>>);
-    fn set_index_buffer(&mut self, index_list: Vec<usize>);
-    fn set_render_target(&mut self, render_target: Arc<Mutex<RenderTarget>>);
-    fn set_constant_buffer(&mut self, layout_index: usize, buffer: Arc<(dyn Any + Send + Sync)>);
-    fn draw(&mut self, draw_with_index: bool);
-    fn clear(&self, color: &Vec4);
+    fn create_buffer(&mut self, id: BufferId, data: BufferData) -> Result<(), GpuError>;
+    fn update_buffer(&mut self, id: BufferId, data: BufferData) -> Result<(), GpuError>;
+    fn destroy_buffer(&mut self, id: BufferId) -> Result<(), GpuError>;
+    fn create_texture(&mut self, id: TextureId, texture: Arc<Texture>) -> Result<(), GpuError>;
+    fn update_texture(&mut self, id: TextureId, texture: Arc<Texture>) -> Result<(), GpuError>;
+    fn destroy_texture(&mut self, id: TextureId) -> Result<(), GpuError>;
+    fn create_render_target(
+        &mut self,
+        id: RenderTargetId,
+        width: u32,
+        height: u32,
+        sample_cnt: u32,
+        format: PixelFormat,
+    ) -> Result<(), GpuError>;
+    fn destroy_render_target(&mut self, id: RenderTargetId) -> Result<(), GpuError>;
+    fn read_render_target(&self, id: RenderTargetId) -> Result<RenderTarget, GpuError>;
+    fn create_pipeline_state(
+        &mut self,
+        id: PipelineStateId,
+        pipeline_state: PipelineStateData,
+    ) -> Result<(), GpuError>;
+    fn destroy_pipeline_state(&mut self, id: PipelineStateId) -> Result<(), GpuError>;
+    fn set_vertex_buffer(&mut self, buffer: BufferId) -> Result<(), GpuError>;
+    fn set_index_buffer(&mut self, buffer: BufferId) -> Result<(), GpuError>;
+    fn set_render_target(&mut self, render_target: RenderTargetId) -> Result<(), GpuError>;
+    fn set_render_targets(
+        &mut self,
+        color_attachments: &[RenderTargetId],
+        depth_attachment: Option<RenderTargetId>,
+    ) -> Result<(), GpuError>;
+    fn set_pipeline(&mut self, pipeline_state: PipelineStateId) -> Result<(), GpuError>;
+    fn set_constant_buffer(
+        &mut self,
+        layout_index: usize,
+        buffer: Arc<dyn Any + Send + Sync>,
+    ) -> Result<(), GpuError>;
+    fn set_texture(&mut self, layout_index: usize, texture: TextureId) -> Result<(), GpuError>;
+    fn set_rasterizer_state(&mut self, rasterizer_state: RasterizerState);
+    fn set_depth_stencil_state(&mut self, depth_stencil_state: DepthStencilState);
+    fn set_blend_state(&mut self, blend_state: BlendState);
+    fn set_attachment_blend_state(
+        &mut self,
+        attachment: usize,
+        blend_state: BlendState,
+    ) -> Result<(), GpuError>;
+    fn set_primitive_topology(&mut self, primitive_topology: PrimitiveTopology);
+    fn set_viewport(&mut self, viewport: Viewport);
+    fn set_scissor(&mut self, scissor: Option<Rect>);
+    fn draw(&mut self, draw_with_index: bool) -> Result<(), GpuError>;
+    fn clear(
+        &mut self,
+        flags: ClearFlags,
+        color: &Vec4,
+        depth: f32,
+        stencil: u8,
+    ) -> Result<(), GpuError>;
+    fn resolve(&mut self, dst: RenderTargetId) -> Result<(), GpuError>;
     fn swap(&mut self);
 }
 
-pub struct Gpu<'a, VSInput, VSOutput> {
+/// Draws with the pipeline states created on it, every pipeline state brings its own vertex
+/// types, so buffers of different vertex formats can be drawn one after another.
+pub struct Gpu<'a> {
     bus_mutex: &'a BusMutex<'a>,
-    pipe_line: PipeLine<VSInput, VSOutput>,
+    pipe_line: PipeLine,
     exit_condvar: &'a ExitNotifyCondVar,
-    vertex_list: Option<Vec<VSInput>>,
-    index_list: Option<Vec<usize>>,
-    render_target: Option<Arc<Mutex<RenderTarget>>>,
-    constant_buffer: Vec<Option<Arc<dyn Any + Send>>>,
+    buffers: ResourceTable<BufferData>,
+    textures: ResourceTable<Texture, Arc<Texture>>,
+    render_targets: ResourceTable<RenderTarget>,
+    pipeline_states: ResourceTable<PipelineStateData, Box<dyn AnyPipelineState>>,
+    vertex_buffer: Option<BufferId>,
+    index_buffer: Option<BufferId>,
+    color_attachments: Vec<RenderTargetId>,
+    depth_attachment: Option<RenderTargetId>,
+    pipeline_state: Option<PipelineStateId>,
+    // textures bound to constant buffer slots, looked up when a draw starts
+    texture_slots: Vec<Option<TextureId>>,
+    constant_buffer: Vec<Option<Arc<dyn Any + Send + Sync>>>,
+    // the shaders see the slots as they were when a draw last initialised them
+    constant_buffers_dirty: bool,
     exit_flag: Arc<Mutex<bool>>,
     render_complete_condvar: &'a RenderCompleteNotifyCondVar,
     render_cnt: i32,
+    thread_cnt: usize,
 }
 
-impl<'a, VSInput, VSOutput> Gpu<'a, VSInput, VSOutput> {
+impl<'a> Gpu<'a> {
     pub fn new(
         bus_mutex: &'a BusMutex<'a>,
         condvar: &'a ExitNotifyCondVar,
         render_complete_condvar: &'a RenderCompleteNotifyCondVar,
         exit_flag: Arc<Mutex<bool>>,
-    ) -> Gpu<'a, VSInput, VSOutput> {
+    ) -> Gpu<'a> {
         let mut constant_buffer = vec![];
-        for _ in 0..32 {
+        for _ in 0..CONSTANT_BUFFER_SLOT_CNT {
             constant_buffer.push(None);
         }
 
         Gpu {
             bus_mutex,
-            pipe_line: PipeLine::new(None, None),
+            pipe_line: PipeLine::new(),
             exit_condvar: condvar,
-            vertex_list: None,
-            index_list: None,
-            render_target: None,
+            buffers: ResourceTable::new(),
+            textures: ResourceTable::new(),
+            render_targets: ResourceTable::new(),
+            pipeline_states: ResourceTable::new(),
+            vertex_buffer: None,
+            index_buffer: None,
+            color_attachments: vec![],
+            depth_attachment: None,
+            pipeline_state: None,
+            texture_slots: vec![None; CONSTANT_BUFFER_SLOT_CNT],
             constant_buffer, // 31 is the max constant buffer index
+            constant_buffers_dirty: true,
             exit_flag,
             render_complete_condvar,
             render_cnt: 0,
+            thread_cnt: thread::available_parallelism().map_or(1, |n| n.get()),
         }
     }
 
-    pub fn bind_vertex_shader(
+    fn check_constant_buffer_slot(layout_index: usize) -> Result<(), GpuError> {
+        if layout_index < CONSTANT_BUFFER_SLOT_CNT {
+            Ok(())
+        } else {
+            Err(GpuError::InvalidResource(format!(
+                "constant buffer slot {} is out of range",
+                layout_index
+            )))
+        }
+    }
+
+    // the planes of the bound render targets, looked up apart from the Gpu, so that a draw
+    // can read the vertex buffers meanwhile
+    fn attachments<'t>(
+        render_targets: &'t mut ResourceTable<RenderTarget>,
+        color_attachments: &[RenderTargetId],
+        depth_attachment: Option<RenderTargetId>,
+    ) -> Result<Attachments<'t>, GpuError> {
+        // the depth attachment follows the color attachments, unless it is one of them
+        let mut targets = color_attachments.to_vec();
+        let depth = depth_attachment.map(|depth| {
+            targets
+                .iter()
+                .position(|&id| id == depth)
+                .unwrap_or_else(|| {
+                    targets.push(depth);
+                    targets.len() - 1
+                })
+        });
+        if targets.is_empty() {
+            return Err(GpuError::NotBound("render target"));
+        }
+        let targets = render_targets.get_many_mut(&targets)?;
+        Ok(Attachments::new(targets, color_attachments.len(), depth))
+    }
+
+    /// Sets how many threads shade the tiles of a draw.
+    pub fn set_thread_cnt(&mut self, thread_cnt: usize) {
+        self.thread_cnt = thread_cnt.max(1);
+    }
+}
+
+impl<'a> GpuApi<'a> for Gpu<'a> {
+    fn create_buffer(&mut self, id: BufferId, data: BufferData) -> Result<(), GpuError> {
+        self.buffers.insert(id, data)
+    }
+
+    fn update_buffer(&mut self, id: BufferId, data: BufferData) -> Result<(), GpuError> {
+        let old = self.buffers.get_mut(id)?;
+        let same_kind = match (&*old, &data) {
+            (BufferData::Vertex(old), BufferData::Vertex(new)) => {
+                (**old).type_id() == (**new).type_id()
+            }
+            (BufferData::Index(_), BufferData::Index(_)) => true,
+            _ => false,
+        };
+        if !same_kind {
+            return Err(GpuError::InvalidResource(format!(
+                "{:?} cannot change its kind or vertex type",
+                id
+            )));
+        }
+        *old = data;
+        Ok(())
+    }
+
+    fn destroy_buffer(&mut self, id: BufferId) -> Result<(), GpuError> {
+        self.buffers.remove(id).map(|_| ())
+    }
+
+    fn create_texture(&mut self, id: TextureId, texture: Arc<Texture>) -> Result<(), GpuError> {
+        self.textures.insert(id, texture)
+    }
+
+    fn update_texture(&mut self, id: TextureId, texture: Arc<Texture>) -> Result<(), GpuError> {
+        *self.textures.get_mut(id)? = texture;
+        self.constant_buffers_dirty = true;
+        Ok(())
+    }
+
+    fn destroy_texture(&mut self, id: TextureId) -> Result<(), GpuError> {
+        self.textures.remove(id)?;
+        self.constant_buffers_dirty = true;
+        Ok(())
+    }
+
+    fn create_render_target(
         &mut self,
-        vertex_shader: Box<dyn VertexShader<VSInput, VSOutput> + Send + Sync>,
-    ) {
-        self.pipe_line.bind_vertex_shader(Some(vertex_shader));
+        id: RenderTargetId,
+        width: u32,
+        height: u32,
+        sample_cnt: u32,
+        format: PixelFormat,
+    ) -> Result<(), GpuError> {
+        if width == 0 || height == 0 {
+            return Err(GpuError::InvalidResource(format!(
+                "render target size {}x{} is empty",
+                width, height
+            )));
+        }
+        if ![1, 2, 4, 8].contains(&sample_cnt) {
+            return Err(GpuError::InvalidResource(format!(
+                "unsupported sample count: {}",
+                sample_cnt
+            )));
+        }
+        match RenderTarget::sample_len(width, height, sample_cnt) {
+            Some(len) if len <= MAX_RENDER_TARGET_SAMPLES => {}
+            _ => {
+                return Err(GpuError::InvalidResource(format!(
+                    "render target size {}x{} at {}x is too large",
+                    width, height, sample_cnt
+                )))
+            }
+        }
+        let render_target = RenderTarget::new_with_format(width, height, sample_cnt, format);
+        self.render_targets.insert(id, render_target)
+    }
+
+    fn destroy_render_target(&mut self, id: RenderTargetId) -> Result<(), GpuError> {
+        self.render_targets.remove(id).map(|_| ())
     }
 
-    pub fn bind_pixel_shader(
+    fn read_render_target(&self, id: RenderTargetId) -> Result<RenderTarget, GpuError> {
+        self.render_targets.get(id).cloned()
+    }
+
+    fn create_pipeline_state(
         &mut self,
-        pixel_shader: Box<dyn PixelShader<VSOutput> + Send + Sync>,
-    ) {
-        self.pipe_line.bind_pixel_shader(Some(pixel_shader));
+        id: PipelineStateId,
+        pipeline_state: PipelineStateData,
+    ) -> Result<(), GpuError> {
+        self.pipeline_states.insert(id, pipeline_state.into_inner())
     }
-}
 
-impl<'a, VSInput, VSOutput> GpuApi<'a> for Gpu<'a, VSInput, VSOutput>
-where
-    VSInput: 'static + Sync + Send + Debug + Copy + Clone,
-    VSOutput: 'static + VertexShaderOutputPositionAndLerp + Sync + Send + Debug + Copy + Clone,
-{
-    fn set_vertex_buffer(&mut self, vertex_list: Vec<Arc<dyn Any + Send + Sync>>) {
-        let vertex_list = vertex_list
+    fn destroy_pipeline_state(&mut self, id: PipelineStateId) -> Result<(), GpuError> {
+        self.pipeline_states.remove(id)?;
+        if self.pipeline_state == Some(id) {
+            self.pipeline_state = None;
+        }
+        Ok(())
+    }
+
+    fn set_vertex_buffer(&mut self, buffer: BufferId) -> Result<(), GpuError> {
+        match self.buffers.get(buffer)? {
+            BufferData::Vertex(_) => {
+                self.vertex_buffer = Some(buffer);
+                Ok(())
+            }
+            BufferData::Index(_) => Err(GpuError::InvalidResource(format!(
+                "{:?} is not a vertex buffer",
+                buffer
+            ))),
+        }
+    }
+
+    fn set_index_buffer(&mut self, buffer: BufferId) -> Result<(), GpuError> {
+        match self.buffers.get(buffer)? {
+            BufferData::Index(_) => {
+                self.index_buffer = Some(buffer);
+                Ok(())
+            }
+            BufferData::Vertex(_) => Err(GpuError::InvalidResource(format!(
+                "{:?} is not an index buffer",
+                buffer
+            ))),
+        }
+    }
+
+    fn set_render_target(&mut self, render_target: RenderTargetId) -> Result<(), GpuError> {
+        self.set_render_targets(&[render_target], Some(render_target))
+    }
+
+    fn set_render_targets(
+        &mut self,
+        color_attachments: &[RenderTargetId],
+        depth_attachment: Option<RenderTargetId>,
+    ) -> Result<(), GpuError> {
+        if color_attachments.len() > MAX_COLOR_ATTACHMENTS {
+            return Err(GpuError::InvalidResource(format!(
+                "{} color attachments are more than {}",
+                color_attachments.len(),
+                MAX_COLOR_ATTACHMENTS
+            )));
+        }
+        let mut format = None;
+        for (i, &id) in color_attachments
             .iter()
-            .map(|vertex| {
-                let vt = vertex.downcast_ref::<VSInput>();
-                if let None = vt {
-                    panic!("vertex type is not matched: {:?}", Any::type_id(vertex));
-                }
-                *(vt.unwrap())
-            })
-            .collect::<Vec<VSInput>>();
-        self.vertex_list = Some(vertex_list);
+            .chain(&depth_attachment)
+            .enumerate()
+        {
+            let render_target = self.render_targets.get(id)?;
+            if i < color_attachments.len() && color_attachments[..i].contains(&id) {
+                return Err(GpuError::InvalidResource(format!("{:?} is used twice", id)));
+            }
+            let target_format = (
+                render_target.width(),
+                render_target.height(),
+                render_target.sample_cnt(),
+            );
+            if *format.get_or_insert(target_format) != target_format {
+                return Err(GpuError::InvalidResource(format!(
+                    "{:?} does not match the size and sample count of the other attachments",
+                    id
+                )));
+            }
+        }
+        if format.is_none() {
+            return Err(GpuError::InvalidResource(
+                "a draw needs a color or a depth attachment".to_string(),
+            ));
+        }
+
+        self.color_attachments = color_attachments.to_vec();
+        self.depth_attachment = depth_attachment;
+        Ok(())
     }
 
-    fn set_render_target(&mut self, render_target: Arc<Mutex<RenderTarget>>) {
-        self.render_target = Some(render_target);
+    fn set_pipeline(&mut self, pipeline_state: PipelineStateId) -> Result<(), GpuError> {
+        self.pipeline_states
+            .get(pipeline_state)?
+            .apply(&mut self.pipe_line);
+        self.pipeline_state = Some(pipeline_state);
+        self.constant_buffers_dirty = true;
+        Ok(())
     }
 
-    fn set_constant_buffer(&mut self, layout_index: usize, buffer: Arc<(dyn Any + Send + Sync)>) {
+    fn set_constant_buffer(
+        &mut self,
+        layout_index: usize,
+        buffer: Arc<dyn Any + Send + Sync>,
+    ) -> Result<(), GpuError> {
+        // the slot is checked against the pipeline state the next draw runs with, so it
+        // can be bound before the pipeline state that reads it
+        Self::check_constant_buffer_slot(layout_index)?;
+        self.texture_slots[layout_index] = None;
         self.constant_buffer[layout_index] = Some(buffer);
+        self.constant_buffers_dirty = true;
+        Ok(())
     }
 
-    fn swap(&mut self) {
-        self.render_cnt += 1;
+    fn set_texture(&mut self, layout_index: usize, texture: TextureId) -> Result<(), GpuError> {
+        Self::check_constant_buffer_slot(layout_index)?;
+        self.textures.get(texture)?;
+        self.texture_slots[layout_index] = Some(texture);
+        self.constant_buffers_dirty = true;
+        Ok(())
+    }
 
-        let (mutex, condvar) = self.render_complete_condvar.as_ref();
-        let mut guard = mutex.lock().unwrap();
-        *guard = self.render_cnt;
+    fn set_rasterizer_state(&mut self, rasterizer_state: RasterizerState) {
+        self.pipe_line.set_rasterizer_state(rasterizer_state);
+    }
 
-        condvar.notify_all();
+    fn set_depth_stencil_state(&mut self, depth_stencil_state: DepthStencilState) {
+        self.pipe_line.set_depth_stencil_state(depth_stencil_state);
     }
 
-    fn draw(&mut self, draw_with_index: bool) {
-        if let None = self.vertex_list {
-            panic!("vertex buffer is not set");
-        }
+    fn set_blend_state(&mut self, blend_state: BlendState) {
+        self.pipe_line.set_blend_state(blend_state);
+    }
 
-        if let None = self.render_target {
-            panic!("render target is not set");
+    fn set_attachment_blend_state(
+        &mut self,
+        attachment: usize,
+        blend_state: BlendState,
+    ) -> Result<(), GpuError> {
+        if attachment >= MAX_COLOR_ATTACHMENTS {
+            return Err(GpuError::InvalidResource(format!(
+                "color attachment {} is out of range",
+                attachment
+            )));
         }
+        self.pipe_line
+            .set_attachment_blend_state(attachment, blend_state);
+        Ok(())
+    }
 
-        let mut render_target = self.render_target.as_ref().unwrap().lock().unwrap();
-        let viewport_mat = Mat4x4::viewport_mat(
-            0,
-            0,
-            render_target.width() as i32,
-            render_target.height() as i32,
-        );
-
-        let pipe_line = &mut self.pipe_line;
-        let handled_vertex_list = self
-            .vertex_list
-            .as_ref()
-            .unwrap()
-            .iter()
-            .map(|vertex| {
-                let mut vt = pipe_line.handle_vertex_shader(vertex, &self.constant_buffer);
+    fn set_primitive_topology(&mut self, primitive_topology: PrimitiveTopology) {
+        self.pipe_line.set_primitive_topology(primitive_topology);
+    }
 
-                // apply perspective division
-                let mut v = vt.position_as_mut().clone();
-                v /= v.w;
-                v.w = 1.0;
-                v.z = (v.z + 1.0) / 2.0;
-                // println!("v: {:?}", v);
+    fn set_viewport(&mut self, viewport: Viewport) {
+        self.pipe_line.set_viewport(viewport);
+    }
 
-                // apply viewport transform
-                v = viewport_mat * v;
-                *(vt.position_as_mut()) = v;
+    fn set_scissor(&mut self, scissor: Option<Rect>) {
+        self.pipe_line.set_scissor(scissor);
+    }
 
-                // println!("vt: {:?}", vt);
+    fn swap(&mut self) {
+        self.render_cnt += 1;
 
-                return vt;
-            })
-            .collect::<Vec<VSOutput>>();
+        let (mutex, condvar) = self.render_complete_condvar.as_ref();
+        let mut guard = mutex.lock().unwrap();
+        *guard = self.render_cnt;
 
-        let vertex_cnt = if draw_with_index {
-            self.index_list.as_ref().unwrap().len()
-        } else {
-            self.vertex_list.as_ref().unwrap().len()
-        };
+        condvar.notify_all();
+    }
 
-        let triangles = vertex_cnt / 3;
-
-        if !draw_with_index {
-            for i in 0..triangles {
-                let v0 = &handled_vertex_list[i * 3];
-                let v1 = &handled_vertex_list[i * 3 + 1];
-                let v2 = &handled_vertex_list[i * 3 + 2];
-
-                RenderUtil::draw_triangle(
-                    render_target.deref_mut(),
-                    v0,
-                    v1,
-                    v2,
-                    |v0: &VSOutput, v1: &VSOutput, weight: f32| {
-                        // println!("weight: {:?}", weight);
-                        let color = VSOutput::lerp(v0, v1, weight);
-                        let output = pipe_line.handle_pixel_shader(&color, &self.constant_buffer);
-                        return output;
-                    },
-                );
+    fn draw(&mut self, draw_with_index: bool) -> Result<(), GpuError> {
+        let pipeline_state = self
+            .pipeline_state
+            .ok_or(GpuError::NotBound("pipeline state"))?;
+        let vertex_buffer = self
+            .vertex_buffer
+            .ok_or(GpuError::NotBound("vertex buffer"))?;
+        let vertices = match self.buffers.get(vertex_buffer)? {
+            BufferData::Vertex(vertices) => vertices.as_ref(),
+            BufferData::Index(_) => unreachable!("a buffer keeps its kind"),
+        };
+        let indices = if draw_with_index {
+            let index_buffer = self
+                .index_buffer
+                .ok_or(GpuError::NotBound("index buffer"))?;
+            match self.buffers.get(index_buffer)? {
+                BufferData::Index(indices) => Some(indices),
+                BufferData::Vertex(_) => unreachable!("a buffer keeps its kind"),
             }
         } else {
-            let index_list = self.index_list.as_ref().unwrap();
-            for i in 0..triangles {
-                let v0 = &handled_vertex_list[index_list[i * 3]];
-                let v1 = &handled_vertex_list[index_list[i * 3 + 1]];
-                let v2 = &handled_vertex_list[index_list[i * 3 + 2]];
-
-                RenderUtil::draw_triangle(
-                    render_target.deref_mut(),
-                    v0,
-                    v1,
-                    v2,
-                    |v0: &VSOutput, v1: &VSOutput, weight: f32| {
-                        let color = VSOutput::lerp(v0, v1, weight);
-                        // println!("weight: {:?}", weight);
-                        let output = pipe_line.handle_pixel_shader(&color, &self.constant_buffer);
-                        return output;
-                    },
-                );
+            None
+        };
+        if self.constant_buffers_dirty {
+            let mut slots = self.constant_buffer.clone();
+            for (layout_index, texture) in self.texture_slots.iter().enumerate() {
+                if let Some(texture) = texture {
+                    let texture = Arc::clone(self.textures.get(*texture)?);
+                    slots[layout_index] = Some(Arc::new(texture));
+                }
             }
+            let constant_buffers = ConstantBuffers::new(slots);
+            let pipeline_state = self.pipeline_states.get_mut(pipeline_state)?;
+            pipeline_state.validate_constant_buffers(&constant_buffers)?;
+            pipeline_state.init_constant_buffers(&constant_buffers);
+            self.constant_buffers_dirty = false;
         }
+
+        let pipeline_state = self.pipeline_states.get(pipeline_state)?;
+        let attachments = Self::attachments(
+            &mut self.render_targets,
+            &self.color_attachments,
+            self.depth_attachment,
+        )?;
+        pipeline_state.draw(DrawCall {
+            vertex_buffer,
+            vertices,
+            indices,
+            pipe_line: &self.pipe_line,
+            attachments,
+            thread_cnt: self.thread_cnt,
+        })
     }
 
-    fn clear(&self, color: &Vec4) {
-        if let None = self.render_target {
-            panic!("render target is not set");
+    fn clear(
+        &mut self,
+        flags: ClearFlags,
+        color: &Vec4,
+        depth: f32,
+        stencil: u8,
+    ) -> Result<(), GpuError> {
+        if self.color_attachments.is_empty() && self.depth_attachment.is_none() {
+            return Err(GpuError::NotBound("render target"));
         }
-
-        let mut unwrap = self.render_target.as_ref().unwrap().lock().unwrap();
-        unwrap.clear(Color::new_rgba(
-            color.x as u8,
-            color.y as u8,
-            color.z as u8,
-            color.w as u8,
-        ));
+        if flags.contains(ClearFlags::COLOR) {
+            for &id in &self.color_attachments {
+                self.render_targets.get_mut(id)?.clear_color(color);
+            }
+        }
+        if let Some(id) = self.depth_attachment {
+            let depth_target = self.render_targets.get_mut(id)?;
+            if flags.contains(ClearFlags::DEPTH) {
+                depth_target.clear_depth(depth);
+            }
+            if flags.contains(ClearFlags::STENCIL) {
+                depth_target.clear_stencil(stencil);
+            }
+        }
+        Ok(())
     }
 
-    fn set_index_buffer(&mut self, index_list: Vec<usize>) {
-        self.index_list = Some(index_list);
+    fn resolve(&mut self, dst: RenderTargetId) -> Result<(), GpuError> {
+        let src = *self
+            .color_attachments
+            .first()
+            .ok_or(GpuError::NotBound("render target"))?;
+        let (src, dst) = self.render_targets.get_pair_mut(src, dst)?;
+        if (src.width(), src.height()) != (dst.width(), dst.height()) {
+            return Err(GpuError::InvalidResource(format!(
+                "cannot resolve a {}x{} render target into a {}x{} one",
+                src.width(),
+                src.height(),
+                dst.width(),
+                dst.height()
+            )));
+        }
+        if dst.sample_cnt() != 1 {
+            return Err(GpuError::InvalidResource(
+                "cannot resolve into a multisampled render target".to_string(),
+            ));
+        }
+        if src.format() != dst.format() {
+            return Err(GpuError::InvalidResource(format!(
+                "cannot resolve a {:?} render target into a {:?} one",
+                src.format(),
+                dst.format()
+            )));
+        }
+        src.resolve(dst);
+        Ok(())
     }
 }
 
-unsafe impl<'a, VSInput, VSOutput> Sync for Gpu<'a, VSInput, VSOutput> {}
+unsafe impl<'a> Sync for Gpu<'a> {}
 
-unsafe impl<'a, VSInput, VSOutput> Send for Gpu<'a, VSInput, VSOutput> {}
+unsafe impl<'a> Send for Gpu<'a> {}
 
-impl<'a, VSInput, VSOutput> Unit for Gpu<'a, VSInput, VSOutput>
-where
-    VSInput: 'static + Debug + Sync + Send + Copy + Clone,
-    VSOutput: 'static + VertexShaderOutputPositionAndLerp + Debug + Sync + Send + Copy + Clone,
-{
+impl<'a> Unit for Gpu<'a> {
     fn init(&mut self) {}
 
     fn start(&mut self) {
@@ -271,7 +604,9 @@ where
             };
 
             // print!("gpu get cmd: {:?}\n", cmd.as_ref().cmd_type());
-            cmd.execute(self);
+            if let Err(err) = cmd.execute(self) {
+                eprintln!("gpu: {:?} failed: {}", cmd.cmd_type(), err);
+            }
         }
 
         self.exit();
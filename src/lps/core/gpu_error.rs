@@ -0,0 +1,45 @@
+use std::error::Error;
+use std::fmt;
+
+/// Why the Gpu could not execute a command. Handles are given by their debug output.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GpuError {
+    /// The handle does not name a resource that was created.
+    UnknownHandle(String),
+    /// The resource of the handle was destroyed, the handle must not be used anymore.
+    StaleHandle(String),
+    /// A resource was created under a handle that still names another one.
+    HandleInUse(String),
+    /// The resource does not fit its use, like an index buffer bound as vertex buffer.
+    InvalidResource(String),
+    /// The command needs a resource that is not bound.
+    NotBound(&'static str),
+    /// The value bound to a constant buffer slot is not of the type a shader declares.
+    ConstantBufferType { slot: usize, expected: &'static str },
+    /// A shader declares a constant buffer slot nothing is bound to.
+    ConstantBufferMissing { slot: usize, expected: &'static str },
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::UnknownHandle(handle) => write!(f, "{} does not exist", handle),
+            GpuError::StaleHandle(handle) => write!(f, "{} was destroyed", handle),
+            GpuError::HandleInUse(handle) => write!(f, "{} is already in use", handle),
+            GpuError::InvalidResource(message) => write!(f, "{}", message),
+            GpuError::NotBound(resource) => write!(f, "{} is not bound", resource),
+            GpuError::ConstantBufferType { slot, expected } => {
+                write!(f, "constant buffer {} is not a {}", slot, expected)
+            }
+            GpuError::ConstantBufferMissing { slot, expected } => {
+                write!(
+                    f,
+                    "constant buffer {} expects a {}, but none is bound",
+                    slot, expected
+                )
+            }
+        }
+    }
+}
+
+impl Error for GpuError {}
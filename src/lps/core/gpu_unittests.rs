@@ -0,0 +1,1600 @@
+#[cfg(test)]
+mod tests {
+    use crate::create_box;
+    use crate::lps::common::color::Color;
+    use crate::lps::common::math::mat4x4::Mat4x4;
+    use crate::lps::common::math::vec2::Vec2;
+    use crate::lps::common::math::vec3::Vec3;
+    use crate::lps::common::math::vec4::Vec4;
+    use crate::lps::common::mesh::{Mesh, MeshShared};
+    use crate::lps::common::rect::Rect;
+    use crate::lps::common::sampler::Sampler;
+    use crate::lps::common::texture::Texture;
+    use crate::lps::core::bus::Bus;
+    use crate::lps::core::gpu::{Gpu, GpuApi};
+    use crate::lps::core::gpu_error::GpuError;
+    use crate::lps::rasterize::blend_state::{BlendState, ColorWriteMask};
+    use crate::lps::rasterize::buffer::{BufferData, IndexBuffer};
+    use crate::lps::rasterize::constant_buffer::{ConstantBufferBinding, ConstantBuffers};
+    use crate::lps::rasterize::depth_stencil_state::{
+        CompareFunc, DepthStencilState, StencilFaceState, StencilOp,
+    };
+    use crate::lps::rasterize::pipeline::{PixelShader, VertexShader};
+    use crate::lps::rasterize::pipeline_state::{
+        AnyPipelineState, PipelineState, PipelineStateData,
+    };
+    use crate::lps::rasterize::pixel_format::PixelFormat;
+    use crate::lps::rasterize::pixel_output::PixelOutput;
+    use crate::lps::rasterize::pixel_quad::PixelQuad;
+    use crate::lps::rasterize::pixel_shader::CustomPixelShader;
+    use crate::lps::rasterize::primitive_topology::PrimitiveTopology;
+    use crate::lps::rasterize::rasterizer_state::{
+        CullMode, FillMode, FrontFace, ProvokingVertex, RasterizerState,
+    };
+    use crate::lps::rasterize::render_cmds::clear::ClearFlags;
+    use crate::lps::rasterize::render_target::RenderTarget;
+    use crate::lps::rasterize::resource::{
+        BufferId, Handle, PipelineStateId, RenderTargetId, TextureId,
+    };
+    use crate::lps::rasterize::vertex_shader::{CustomVertexShader, Transforms};
+    use crate::lps::rasterize::viewport::Viewport;
+    use crate::lps::rasterize::vt_input::VertexShaderInput;
+    use crate::lps::rasterize::vt_output::{Varyings, VertexShaderOutput};
+    use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+    use std::sync::{Arc, Condvar, Mutex};
+
+    const TARGET_SIZE: u32 = 128;
+
+    fn create_checker_texture() -> Texture {
+        let size = 64;
+        let mut data = vec![];
+        for y in 0..size {
+            for x in 0..size {
+                if (x / 16 + y / 16) % 2 == 0 {
+                    data.push(Color::new_rgba(255, 255, 255, 255));
+                } else {
+                    data.push(Color::new_rgba(255, 0, 0, 255));
+                }
+            }
+        }
+        Texture::new_with_data(size, size, data)
+    }
+
+    fn create_vertex(x: f32, y: f32, w: f32, u: f32, v: f32) -> VertexShaderInput {
+        VertexShaderInput::new(
+            Vec4::new(x, y, 0.0, w),
+            Vec3::ZERO,
+            Vec2::new(u, v),
+            Vec3::ZERO,
+        )
+    }
+
+    // every helper creates its resources under fresh handles
+    fn next_handle<T>() -> Handle<T> {
+        static NEXT_INDEX: AtomicU32 = AtomicU32::new(0);
+        Handle::new(NEXT_INDEX.fetch_add(1, Ordering::Relaxed), 0)
+    }
+
+    // runs `f` on a Gpu drawing into a cleared render target and returns the target
+    fn with_gpu<F>(width: u32, height: u32, sample_cnt: u32, texture: Texture, f: F) -> RenderTarget
+    where
+        F: FnOnce(&mut Gpu),
+    {
+        let bus = Arc::new(Mutex::new(Bus::new()));
+        let exit_condvar = Arc::new((Mutex::new(0), Condvar::new()));
+        let render_complete_condvar = Arc::new((Mutex::new(0), Condvar::new()));
+        let mut gpu = Gpu::new(
+            &bus,
+            &exit_condvar,
+            &render_complete_condvar,
+            Arc::new(Mutex::new(false)),
+        );
+        let pipeline_state = create_pipeline_state(&mut gpu, custom_pipeline_state());
+        gpu.set_pipeline(pipeline_state).unwrap();
+
+        let render_target: RenderTargetId = next_handle();
+        gpu.create_render_target(render_target, width, height, sample_cnt, PixelFormat::Rgba8)
+            .unwrap();
+        gpu.set_render_target(render_target).unwrap();
+        let identity = Mat4x4::identity();
+        let transforms = Transforms::new(identity, identity, identity);
+        gpu.set_constant_buffer(0, Arc::new(transforms)).unwrap();
+        set_texture(&mut gpu, texture);
+        gpu.clear(
+            ClearFlags::COLOR | ClearFlags::DEPTH | ClearFlags::STENCIL,
+            &Vec4::new(0.0, 0.0, 0.0, 255.0),
+            1.0,
+            0,
+        )
+        .unwrap();
+
+        f(&mut gpu);
+        gpu.read_render_target(render_target).unwrap()
+    }
+
+    fn custom_pipeline_state() -> PipelineState<VertexShaderInput, VertexShaderOutput> {
+        PipelineState::new(
+            Box::new(CustomVertexShader::new()),
+            Box::new(CustomPixelShader::new()),
+        )
+    }
+
+    fn create_pipeline_state<VSInput, VSOutput>(
+        gpu: &mut Gpu,
+        pipeline_state: PipelineState<VSInput, VSOutput>,
+    ) -> PipelineStateId
+    where
+        PipelineState<VSInput, VSOutput>: AnyPipelineState + 'static,
+    {
+        let id = next_handle();
+        gpu.create_pipeline_state(id, PipelineStateData::new(pipeline_state))
+            .unwrap();
+        id
+    }
+
+    fn set_texture(gpu: &mut Gpu, texture: Texture) {
+        let id: TextureId = next_handle();
+        gpu.create_texture(id, Arc::new(texture)).unwrap();
+        gpu.set_texture(3, id).unwrap();
+    }
+
+    fn set_mesh(gpu: &mut Gpu, mesh: &dyn MeshShared) {
+        let (vertex_buffer, index_buffer): (BufferId, BufferId) = (next_handle(), next_handle());
+        gpu.create_buffer(vertex_buffer, mesh.vertex_data())
+            .unwrap();
+        gpu.create_buffer(index_buffer, BufferData::Index(mesh.index_data()))
+            .unwrap();
+        gpu.set_vertex_buffer(vertex_buffer).unwrap();
+        gpu.set_index_buffer(index_buffer).unwrap();
+    }
+
+    // A floor-like quad given directly in clip space: the near edge has w = 1, the far
+    // edge w = 4, so texcoord v is strongly non-linear in screen space.
+    fn render_floor_quad(texture: Texture) -> RenderTarget {
+        with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let vertex_list = vec![
+                create_vertex(-0.9, -0.9, 1.0, 0.0, 0.0),
+                create_vertex(0.9, -0.9, 1.0, 1.0, 0.0),
+                create_vertex(0.9, -0.9, 4.0, 1.0, 1.0),
+                create_vertex(-0.9, -0.9, 4.0, 0.0, 1.0),
+            ];
+            set_mesh(
+                gpu,
+                &Mesh::new_with_data(vertex_list, vec![0, 1, 2, 0, 2, 3]),
+            );
+            gpu.draw(true).unwrap();
+        })
+    }
+
+    // covers the render target between the ndc x `left` and `right` at the ndc depth `z`
+    // with a single colored texture
+    fn draw_quad(gpu: &mut Gpu, left: f32, right: f32, z: f32, color: Color) {
+        let texture = Texture::new_with_data(1, 1, vec![color]);
+        set_texture(gpu, texture);
+        let vertex_list = [(left, -1.0), (right, -1.0), (right, 1.0), (left, 1.0)]
+            .map(|(x, y)| {
+                VertexShaderInput::new(Vec4::new(x, y, z, 1.0), Vec3::ZERO, Vec2::ZERO, Vec3::ZERO)
+            })
+            .to_vec();
+        set_mesh(
+            gpu,
+            &Mesh::new_with_data(vertex_list, vec![0, 1, 2, 0, 2, 3]),
+        );
+        gpu.draw(true).unwrap();
+    }
+
+    fn draw_fullscreen_quad(gpu: &mut Gpu, z: f32, color: Color) {
+        draw_quad(gpu, -1.0, 1.0, z, color);
+    }
+
+    fn render_fullscreen_quad(color: Color, blend_state: BlendState) -> RenderTarget {
+        with_gpu(
+            TARGET_SIZE,
+            TARGET_SIZE,
+            1,
+            create_checker_texture(),
+            |gpu| {
+                gpu.set_blend_state(blend_state);
+                draw_fullscreen_quad(gpu, 0.0, color);
+            },
+        )
+    }
+
+    fn assert_all_pixels(render_target: &RenderTarget, expected: Color) {
+        for j in 0..render_target.height() {
+            for i in 0..render_target.width() {
+                assert_eq!(
+                    *render_target.get_pixel(i, j),
+                    expected,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+            }
+        }
+    }
+
+    fn render_box(
+        rasterizer_state: RasterizerState,
+        depth_stencil_state: DepthStencilState,
+        thread_cnt: usize,
+    ) -> RenderTarget {
+        with_gpu(
+            TARGET_SIZE * 2,
+            TARGET_SIZE,
+            1,
+            create_checker_texture(),
+            |gpu| {
+                gpu.set_thread_cnt(thread_cnt);
+                gpu.set_rasterizer_state(rasterizer_state);
+                gpu.set_depth_stencil_state(depth_stencil_state);
+                draw_boxes(gpu);
+            },
+        )
+    }
+
+    fn draw_boxes(gpu: &mut Gpu) {
+        let axis = Vec3::new(1.0, 1.0, 0.0).normal();
+        let model = Mat4x4::rotate_axis_mat(30.0f32.to_radians(), axis);
+        let view = Mat4x4::view_mat(
+            &Vec3::new(0.0, 0.0, 2.0),
+            &Vec3::new(0.0, 0.0, -1.0),
+            &Vec3::new(1.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+        );
+        let proj = Mat4x4::perspective_mat(60.0f32.to_radians(), 2.0, 0.3, 100.0);
+        gpu.set_constant_buffer(0, Arc::new(Transforms::new(model, view, proj)))
+            .unwrap();
+
+        let mut mesh = create_box(&Vec3::new(0.0, 0.0, 0.0), 0.5);
+        mesh.add_mesh(&create_box(&Vec3::new(0.6, 0.1, -0.5), 0.4));
+        mesh.add_mesh(&create_box(&Vec3::new(-0.7, -0.2, 0.2), 0.3));
+        set_mesh(gpu, &mesh);
+        gpu.draw(true).unwrap();
+    }
+
+    fn count_differences(lhs: &RenderTarget, rhs: &RenderTarget) -> usize {
+        let mut cnt = 0;
+        for j in 0..lhs.height() {
+            for i in 0..lhs.width() {
+                if lhs.get_pixel(i, j) != rhs.get_pixel(i, j) || lhs.depth(i, j) != rhs.depth(i, j)
+                {
+                    cnt += 1;
+                }
+            }
+        }
+        cnt
+    }
+
+    // Inverse of the quad's projection: clip (x, w) = (-0.9 + 1.8u, 1 + 3v), y = -0.9.
+    fn reference_texcoord(ndc_x: f32, ndc_y: f32) -> Option<Vec2> {
+        if ndc_y >= 0.0 {
+            return None;
+        }
+        let w = -0.9 / ndc_y;
+        let u = (ndc_x * w + 0.9) / 1.8;
+        let v = (w - 1.0) / 3.0;
+        if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+            Some(Vec2::new(u, v))
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn test_perspective_correct_texture_mapping() {
+        let texture = create_checker_texture();
+        let render_target = render_floor_quad(texture.clone());
+
+        let half = TARGET_SIZE as f32 / 2.0;
+        let mut compared = 0;
+        let mut mismatched = 0;
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                // only compare pixels whose whole neighbourhood samples the same texel,
+                // which keeps the test independent of sub-pixel conventions
+                let mut expected = None;
+                let mut stable = true;
+                for dy in [-1.0, 0.0, 1.0] {
+                    for dx in [-1.0, 0.0, 1.0] {
+                        let ndc_x = (i as f32 + 0.5 + dx - half) / half;
+                        let ndc_y = (half - (j as f32 + 0.5 + dy)) / half;
+                        let color = reference_texcoord(ndc_x, ndc_y)
+                            .map(|texcoord| texture.sample(&Sampler::default(), texcoord));
+                        match (expected, color) {
+                            (_, None) => stable = false,
+                            (None, Some(color)) => expected = Some(color),
+                            (Some(expected), Some(color)) => stable &= expected == color,
+                        }
+                    }
+                }
+
+                if let (true, Some(expected)) = (stable, expected) {
+                    compared += 1;
+                    if *render_target.get_pixel(i, j) != expected {
+                        mismatched += 1;
+                    }
+                }
+            }
+        }
+
+        assert!(compared > 1000, "compared only {} pixels", compared);
+        assert_eq!(
+            mismatched, 0,
+            "{} of {} pixels mismatched",
+            mismatched, compared
+        );
+    }
+
+    #[test]
+    fn test_back_face_culling() {
+        let none = render_box(
+            RasterizerState::new(CullMode::None, FrontFace::CounterClockwise),
+            DepthStencilState::default(),
+            1,
+        );
+        let back = render_box(
+            RasterizerState::new(CullMode::Back, FrontFace::CounterClockwise),
+            DepthStencilState::default(),
+            1,
+        );
+        let front = render_box(
+            RasterizerState::new(CullMode::Front, FrontFace::Clockwise),
+            DepthStencilState::default(),
+            1,
+        );
+        let inside = render_box(
+            RasterizerState::new(CullMode::Front, FrontFace::CounterClockwise),
+            DepthStencilState::default(),
+            1,
+        );
+
+        // the boxes are closed, so culling their back faces must not change the image
+        assert_eq!(count_differences(&none, &back), 0);
+        assert_eq!(count_differences(&none, &front), 0);
+        assert!(count_differences(&none, &inside) > 0);
+    }
+
+    #[test]
+    fn test_multithreaded_draw_is_deterministic() {
+        let single = render_box(RasterizerState::default(), DepthStencilState::default(), 1);
+
+        for thread_cnt in [2, 3, 8] {
+            let multi = render_box(
+                RasterizerState::default(),
+                DepthStencilState::default(),
+                thread_cnt,
+            );
+            assert_eq!(count_differences(&single, &multi), 0);
+        }
+    }
+
+    #[test]
+    fn test_blend_state() {
+        // the render target is cleared to opaque black
+        let color = Color::new_rgba(200, 100, 40, 128);
+
+        let opaque = render_fullscreen_quad(color, BlendState::opaque());
+        assert_all_pixels(&opaque, color);
+
+        let blended = render_fullscreen_quad(color, BlendState::alpha_blend());
+        assert_all_pixels(&blended, Color::new_rgba(100, 50, 20, 191));
+
+        let red_only = BlendState::opaque().with_write_mask(ColorWriteMask::RED);
+        let masked = render_fullscreen_quad(color, red_only);
+        assert_all_pixels(&masked, Color::new_rgba(200, 0, 0, 255));
+    }
+
+    // draws a red quad in front of a green one, then a blue one in between
+    fn render_depth_test(depth_stencil_state: DepthStencilState, clear_depth: f32) -> RenderTarget {
+        with_gpu(
+            TARGET_SIZE,
+            TARGET_SIZE,
+            1,
+            create_checker_texture(),
+            |gpu| {
+                gpu.clear(ClearFlags::DEPTH, &Vec4::ZERO, clear_depth, 0)
+                    .unwrap();
+                gpu.set_depth_stencil_state(depth_stencil_state);
+                draw_fullscreen_quad(gpu, -0.5, Color::new_rgba(255, 0, 0, 255));
+                draw_fullscreen_quad(gpu, 0.5, Color::new_rgba(0, 255, 0, 255));
+                draw_fullscreen_quad(gpu, 0.0, Color::new_rgba(0, 0, 255, 255));
+            },
+        )
+    }
+
+    #[test]
+    fn test_depth_test() {
+        let red = Color::new_rgba(255, 0, 0, 255);
+        let green = Color::new_rgba(0, 255, 0, 255);
+        let blue = Color::new_rgba(0, 0, 255, 255);
+
+        let less = render_depth_test(DepthStencilState::default(), 1.0);
+        assert_all_pixels(&less, red);
+        assert_eq!(less.depth(7, 9), 0.25);
+
+        // reverse-z, the depth buffer is cleared to the near plane
+        let greater = DepthStencilState::new(true, true, CompareFunc::Greater);
+        assert_all_pixels(&render_depth_test(greater, 0.0), green);
+
+        let always = DepthStencilState::new(true, true, CompareFunc::Always);
+        assert_all_pixels(&render_depth_test(always, 1.0), blue);
+
+        let never = DepthStencilState::new(true, true, CompareFunc::Never);
+        let never = render_depth_test(never, 1.0);
+        assert_all_pixels(&never, Color::new_rgba(0, 0, 0, 255));
+
+        // every quad is tested against the cleared depth, which is never written
+        let read_only = DepthStencilState::new(true, false, CompareFunc::Less);
+        let read_only = render_depth_test(read_only, 0.6);
+        assert_all_pixels(&read_only, blue);
+        assert_eq!(read_only.depth(7, 9), 0.6);
+
+        let disabled = DepthStencilState::new(false, true, CompareFunc::Never);
+        let disabled = render_depth_test(disabled, 1.0);
+        assert_all_pixels(&disabled, blue);
+        assert_eq!(disabled.depth(7, 9), 1.0);
+    }
+    #[test]
+    fn test_stencil_mask() {
+        let render_target = with_gpu(
+            TARGET_SIZE,
+            TARGET_SIZE,
+            1,
+            create_checker_texture(),
+            |gpu| {
+                // mark the left half in the stencil buffer only
+                let mark = StencilFaceState::new(
+                    CompareFunc::Always,
+                    StencilOp::Keep,
+                    StencilOp::Keep,
+                    StencilOp::Replace,
+                );
+                let depth_stencil_state = DepthStencilState::new(false, false, CompareFunc::Always)
+                    .with_stencil(1, mark, mark);
+                gpu.set_depth_stencil_state(depth_stencil_state);
+                gpu.set_blend_state(BlendState::opaque().with_write_mask(ColorWriteMask::NONE));
+                draw_quad(gpu, -1.0, 0.0, 0.0, Color::new_rgba(255, 0, 0, 255));
+
+                // then draw where the mark is
+                let test = StencilFaceState::new(
+                    CompareFunc::Equal,
+                    StencilOp::Keep,
+                    StencilOp::Keep,
+                    StencilOp::Keep,
+                );
+                let depth_stencil_state = DepthStencilState::default().with_stencil(1, test, test);
+                gpu.set_depth_stencil_state(depth_stencil_state);
+                gpu.set_blend_state(BlendState::opaque());
+                draw_fullscreen_quad(gpu, 0.0, Color::new_rgba(0, 255, 0, 255));
+            },
+        );
+
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let (color, stencil) = if i < TARGET_SIZE / 2 {
+                    (Color::new_rgba(0, 255, 0, 255), 1)
+                } else {
+                    (Color::new_rgba(0, 0, 0, 255), 0)
+                };
+                assert_eq!(
+                    *render_target.get_pixel(i, j),
+                    color,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+                assert_eq!(render_target.stencil(i, j), stencil, "pixel ({}, {})", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stencil_faces() {
+        let count = |back_op: StencilOp| {
+            let front = StencilFaceState::new(
+                CompareFunc::Always,
+                StencilOp::Keep,
+                StencilOp::Keep,
+                StencilOp::IncrWrap,
+            );
+            let back = StencilFaceState::new(
+                CompareFunc::Always,
+                StencilOp::Keep,
+                StencilOp::Keep,
+                back_op,
+            );
+            let depth_stencil_state = DepthStencilState::new(false, false, CompareFunc::Always)
+                .with_stencil(0, front, back);
+            render_box(RasterizerState::default(), depth_stencil_state, 1)
+        };
+
+        // every ray through the closed boxes enters and leaves them as often
+        let both_incr = count(StencilOp::IncrWrap);
+        let incr_decr = count(StencilOp::DecrWrap);
+        let mut covered = 0;
+        for j in 0..both_incr.height() {
+            for i in 0..both_incr.width() {
+                let stencil = both_incr.stencil(i, j);
+                assert_eq!(stencil % 2, 0, "pixel ({}, {})", i, j);
+                if stencil > 0 {
+                    covered += 1;
+                }
+                assert_eq!(incr_decr.stencil(i, j), 0, "pixel ({}, {})", i, j);
+            }
+        }
+        assert!(covered > 1000, "covered only {} pixels", covered);
+    }
+    #[test]
+    fn test_multisample_resolve() {
+        let mut resolved = None;
+        with_gpu(
+            TARGET_SIZE,
+            TARGET_SIZE,
+            4,
+            create_checker_texture(),
+            |gpu| {
+                // the right edge runs through the pixel centers of the middle column
+                let right = 0.5 / (TARGET_SIZE / 2) as f32;
+                draw_quad(gpu, -1.0, right, 0.0, Color::WHITE);
+
+                // the destination has to be single sampled and of the same size
+                for (size, sample_cnt) in [(TARGET_SIZE / 2, 1), (TARGET_SIZE, 4)] {
+                    let invalid: RenderTargetId = next_handle();
+                    gpu.create_render_target(invalid, size, size, sample_cnt, PixelFormat::Rgba8)
+                        .unwrap();
+                    assert!(matches!(
+                        gpu.resolve(invalid),
+                        Err(GpuError::InvalidResource(_))
+                    ));
+                }
+
+                let render_target: RenderTargetId = next_handle();
+                gpu.create_render_target(
+                    render_target,
+                    TARGET_SIZE,
+                    TARGET_SIZE,
+                    1,
+                    PixelFormat::Rgba8,
+                )
+                .unwrap();
+                gpu.resolve(render_target).unwrap();
+                resolved = Some(gpu.read_render_target(render_target).unwrap());
+            },
+        );
+
+        let render_target = resolved.unwrap();
+
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let expected = match i.cmp(&(TARGET_SIZE / 2)) {
+                    std::cmp::Ordering::Less => Color::WHITE,
+                    std::cmp::Ordering::Equal => Color::new_rgba(128, 128, 128, 255),
+                    std::cmp::Ordering::Greater => Color::BLACK,
+                };
+                assert_eq!(
+                    *render_target.get_pixel(i, j),
+                    expected,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_and_point_topologies() {
+        // texel i of the gradient has red i, the texcoord u is interpolated along the line
+        let gradient = (0..=255)
+            .map(|i| Color::new_rgba(i, 0, 0, 255))
+            .collect::<Vec<Color>>();
+        let render_target = with_gpu(
+            TARGET_SIZE,
+            TARGET_SIZE,
+            1,
+            Texture::new_with_data(256, 1, gradient),
+            |gpu| {
+                // through the pixel centers of row 32
+                let y = 1.0 - 32.5 / (TARGET_SIZE / 2) as f32;
+                let line = vec![
+                    create_vertex(-1.0, y, 1.0, 0.0, 0.0),
+                    create_vertex(1.0, y, 1.0, 1.0, 0.0),
+                ];
+                set_mesh(gpu, &Mesh::new_with_data(line, vec![]));
+                gpu.set_primitive_topology(PrimitiveTopology::LineList);
+                gpu.draw(false).unwrap();
+
+                // on the center of pixel (100, 100), the point outside the target is clipped
+                let x = 100.5 / (TARGET_SIZE / 2) as f32 - 1.0;
+                let points = vec![
+                    create_vertex(x, -x, 1.0, 0.5, 0.0),
+                    create_vertex(1.5, 0.0, 1.0, 0.0, 0.0),
+                ];
+                set_mesh(gpu, &Mesh::new_with_data(points, vec![0, 1]));
+                gpu.set_primitive_topology(PrimitiveTopology::PointList);
+                gpu.draw(true).unwrap();
+            },
+        );
+
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let red = render_target.get_pixel(i, j).r as u32;
+                match (i, j) {
+                    (_, 32) => assert!(
+                        red == i * 2 || red == i * 2 + 1,
+                        "pixel ({}, {}) has red {}",
+                        i,
+                        j,
+                        red
+                    ),
+                    (100, 100) => assert_eq!(red, 128),
+                    _ => assert_eq!(red, 0, "pixel ({}, {})", i, j),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_triangle_strip_keeps_winding() {
+        let render_strip = |front_face: FrontFace| {
+            let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+            with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+                let strip = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+                    .map(|(x, y)| create_vertex(x, y, 1.0, 0.0, 0.0))
+                    .to_vec();
+                set_mesh(gpu, &Mesh::new_with_data(strip, vec![]));
+                gpu.set_rasterizer_state(RasterizerState::new(CullMode::Back, front_face));
+                gpu.set_primitive_topology(PrimitiveTopology::TriangleStrip);
+                gpu.draw(false).unwrap();
+            })
+        };
+
+        // both triangles of the strip face the same way, so one facing draws the whole
+        // target and the other nothing
+        let ccw = render_strip(FrontFace::CounterClockwise);
+        let cw = render_strip(FrontFace::Clockwise);
+        let (drawn, culled) = if *ccw.get_pixel(0, 0) == Color::WHITE {
+            (ccw, cw)
+        } else {
+            (cw, ccw)
+        };
+        assert_all_pixels(&drawn, Color::WHITE);
+        assert_all_pixels(&culled, Color::BLACK);
+    }
+
+    #[test]
+    fn test_wireframe_overlay() {
+        let green = Color::new_rgba(0, 255, 0, 255);
+        let wireframe = RasterizerState::default().with_fill_mode(FillMode::Wireframe);
+        let render_boxes = |solid: bool, rasterizer_state: RasterizerState| {
+            with_gpu(
+                TARGET_SIZE * 2,
+                TARGET_SIZE,
+                1,
+                create_checker_texture(),
+                |gpu| {
+                    if solid {
+                        draw_boxes(gpu);
+                    }
+                    let texture = Texture::new_with_data(1, 1, vec![green]);
+                    set_texture(gpu, texture);
+                    gpu.set_rasterizer_state(rasterizer_state);
+                    gpu.set_depth_stencil_state(DepthStencilState::new(
+                        true,
+                        true,
+                        CompareFunc::LessEqual,
+                    ));
+                    draw_boxes(gpu);
+                },
+            )
+        };
+        let count_green = |render_target: &RenderTarget| {
+            let mut cnt = 0;
+            for j in 0..render_target.height() {
+                for i in 0..render_target.width() {
+                    if *render_target.get_pixel(i, j) == green {
+                        cnt += 1;
+                    }
+                }
+            }
+            cnt
+        };
+
+        let solid = render_boxes(false, RasterizerState::default());
+        let edges = render_boxes(false, wireframe);
+        let overlay = render_boxes(true, wireframe.with_depth_bias(-1e-3));
+        let unbiased = render_boxes(true, wireframe);
+        let points = render_boxes(
+            false,
+            RasterizerState::default().with_fill_mode(FillMode::Point),
+        );
+        let (solid, edges, overlay) = (solid, edges, overlay);
+
+        let edge_cnt = count_green(&edges);
+        assert!(
+            edge_cnt > 100 && edge_cnt < count_green(&solid) / 4,
+            "{} edge pixels",
+            edge_cnt
+        );
+        let point_cnt = count_green(&points);
+        assert!(
+            point_cnt > 0 && point_cnt <= 3 * 8,
+            "{} point pixels",
+            point_cnt
+        );
+
+        // the overlay only shows the visible edges, the hidden ones fail the depth test
+        let mut visible_cnt = 0;
+        for j in 0..overlay.height() {
+            for i in 0..overlay.width() {
+                if *overlay.get_pixel(i, j) == green {
+                    assert_eq!(*edges.get_pixel(i, j), green, "pixel ({}, {})", i, j);
+                    visible_cnt += 1;
+                }
+            }
+        }
+        assert!(
+            visible_cnt > edge_cnt / 3 && visible_cnt < edge_cnt,
+            "{} of {}",
+            visible_cnt,
+            edge_cnt
+        );
+        // without the bias the edges fight with the solid triangles they belong to
+        let unbiased_cnt = count_green(&unbiased);
+        assert!(
+            unbiased_cnt < visible_cnt,
+            "{} of {}",
+            unbiased_cnt,
+            visible_cnt
+        );
+    }
+
+    #[test]
+    fn test_viewport_and_scissor() {
+        let (red, green) = (
+            Color::new_rgba(255, 0, 0, 255),
+            Color::new_rgba(0, 255, 0, 255),
+        );
+        let size = TARGET_SIZE as f32;
+        let render_target = with_gpu(
+            TARGET_SIZE,
+            TARGET_SIZE,
+            1,
+            create_checker_texture(),
+            |gpu| {
+                // split screen, both halves at depth 0.75
+                gpu.set_viewport(Viewport::new(0.0, 0.0, size / 2.0, size));
+                draw_fullscreen_quad(gpu, 0.5, red);
+                gpu.set_viewport(Viewport::new(size / 2.0, 0.0, size / 2.0, size));
+                draw_fullscreen_quad(gpu, 0.5, green);
+
+                // a minimap in the top right corner, its depth range puts it in front
+                let minimap =
+                    Viewport::new(size - 32.0, 0.0, 32.0, 32.0).with_depth_range(0.0, 0.1);
+                gpu.set_viewport(minimap);
+                draw_fullscreen_quad(gpu, 0.9, Color::BLUE);
+
+                gpu.set_viewport(Viewport::new(0.0, 0.0, size, size));
+                gpu.set_scissor(Some(Rect::new(10, 20, 30, 5)));
+                draw_fullscreen_quad(gpu, -1.0, Color::WHITE);
+            },
+        );
+
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let expected = if (10..40).contains(&i) && (20..25).contains(&j) {
+                    Color::WHITE
+                } else if i >= TARGET_SIZE - 32 && j < 32 {
+                    Color::BLUE
+                } else if i < TARGET_SIZE / 2 {
+                    red
+                } else {
+                    green
+                };
+                assert_eq!(
+                    *render_target.get_pixel(i, j),
+                    expected,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_buffers_are_shared_by_handle() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            // the quad's vertices sit behind 70000 unused ones, so the indices need u32
+            let offset = 70000;
+            let mut vertices = vec![create_vertex(0.0, 0.0, 1.0, 0.0, 0.0); offset];
+            vertices.extend(
+                [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                    .map(|(x, y)| create_vertex(x, y, 1.0, 0.0, 0.0)),
+            );
+            let vertices: Arc<[VertexShaderInput]> = Arc::from(vertices);
+            let indices = [0, 1, 2, 0, 2, 3].map(|i| i + offset as u32);
+            let index_buffer = IndexBuffer::new_compact(&indices);
+            assert!(matches!(index_buffer, IndexBuffer::U32(_)));
+
+            let (vertex_buffer, index_buffer_id): (BufferId, BufferId) =
+                (next_handle(), next_handle());
+            gpu.create_buffer(vertex_buffer, BufferData::vertex(Arc::clone(&vertices)))
+                .unwrap();
+            gpu.create_buffer(index_buffer_id, BufferData::Index(index_buffer))
+                .unwrap();
+            gpu.set_vertex_buffer(vertex_buffer).unwrap();
+            gpu.set_index_buffer(index_buffer_id).unwrap();
+            gpu.draw(true).unwrap();
+            // the Gpu keeps the data without copying it
+            assert_eq!(Arc::strong_count(&vertices), 2);
+        });
+
+        assert_all_pixels(&render_target, Color::WHITE);
+    }
+
+    #[test]
+    fn test_indices_are_checked_against_the_vertex_buffer() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let vertex_list = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0)]
+                .map(|(x, y)| create_vertex(x, y, 1.0, 0.0, 0.0))
+                .to_vec();
+            set_mesh(gpu, &Mesh::new_with_data(vertex_list, vec![0, 1, 3]));
+            assert!(matches!(gpu.draw(true), Err(GpuError::InvalidResource(_))));
+        });
+
+        // nothing of the draw is rasterized
+        assert_all_pixels(&render_target, Color::BLACK);
+    }
+
+    #[test]
+    fn test_render_targets_are_checked_when_created() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            for (width, height, sample_cnt) in [
+                (0, TARGET_SIZE, 1),
+                (TARGET_SIZE, 0, 1),
+                (TARGET_SIZE, TARGET_SIZE, 3),
+                (65536, 65536, 1),
+                (16384, 16384, 8),
+            ] {
+                let id: RenderTargetId = next_handle();
+                assert!(matches!(
+                    gpu.create_render_target(id, width, height, sample_cnt, PixelFormat::Rgba8),
+                    Err(GpuError::InvalidResource(_))
+                ));
+                // nothing is created under the handle
+                assert!(gpu.read_render_target(id).is_err());
+            }
+        });
+    }
+
+    #[test]
+    fn test_stale_handles_are_reported() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let quad = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0)]
+                .map(|(x, y)| create_vertex(x, y, 1.0, 0.0, 0.0));
+            let quad: Arc<[VertexShaderInput]> = Arc::from(quad);
+            let old: BufferId = next_handle();
+            gpu.create_buffer(old, BufferData::vertex(Arc::clone(&quad)))
+                .unwrap();
+            gpu.set_vertex_buffer(old).unwrap();
+            gpu.destroy_buffer(old).unwrap();
+
+            // the bound buffer is gone, the draw fails instead of reading it
+            let stale = Err(GpuError::StaleHandle(format!("{:?}", old)));
+            assert_eq!(gpu.draw(false), stale);
+            assert_eq!(gpu.destroy_buffer(old), stale);
+
+            // the next generation reuses the slot, the old handle does not alias it
+            let new = BufferId::new(old.index() as u32, old.generation() + 1);
+            let indices = BufferData::Index(IndexBuffer::new_compact(&[0, 1, 2]));
+            gpu.create_buffer(new, indices).unwrap();
+            assert_eq!(gpu.set_vertex_buffer(old), stale);
+            assert!(matches!(
+                gpu.set_vertex_buffer(new),
+                Err(GpuError::InvalidResource(_))
+            ));
+            assert!(matches!(
+                gpu.update_buffer(new, BufferData::vertex(Arc::clone(&quad))),
+                Err(GpuError::InvalidResource(_))
+            ));
+
+            let vertex_buffer: BufferId = next_handle();
+            gpu.create_buffer(vertex_buffer, BufferData::vertex(quad))
+                .unwrap();
+            gpu.set_vertex_buffer(vertex_buffer).unwrap();
+            let texture: TextureId = next_handle();
+            gpu.create_texture(
+                texture,
+                Arc::new(Texture::new_with_data(1, 1, vec![Color::WHITE])),
+            )
+            .unwrap();
+            gpu.set_texture(3, texture).unwrap();
+            gpu.destroy_texture(texture).unwrap();
+            assert!(matches!(gpu.draw(false), Err(GpuError::StaleHandle(_))));
+        });
+
+        // none of the draws reached the render target
+        assert_all_pixels(&render_target, Color::BLACK);
+    }
+
+    #[test]
+    fn test_constant_buffers_are_checked_against_the_layout() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let triangle = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0)]
+                .map(|(x, y)| create_vertex(x, y, 1.0, 0.0, 0.0))
+                .to_vec();
+            set_mesh(gpu, &Mesh::new_with_data(triangle, vec![]));
+
+            // the vertex shader reads its transforms from slot 0, a wrong bind fails the draw
+            let expected = std::any::type_name::<Transforms>();
+            gpu.set_constant_buffer(0, Arc::new(Mat4x4::identity()))
+                .unwrap();
+            assert_eq!(
+                gpu.draw(false),
+                Err(GpuError::ConstantBufferType { slot: 0, expected })
+            );
+            let identity = Mat4x4::identity();
+            gpu.set_constant_buffer(0, Arc::new(Transforms::new(identity, identity, identity)))
+                .unwrap();
+
+            gpu.set_constant_buffer(3, Arc::new(Vec4::ZERO)).unwrap();
+            assert!(gpu
+                .draw(false)
+                .unwrap_err()
+                .to_string()
+                .starts_with("constant buffer 3 is not a"));
+            set_texture(gpu, Texture::new_with_data(1, 1, vec![Color::WHITE]));
+
+            gpu.set_constant_buffer(4, Arc::new(Vec4::ZERO)).unwrap();
+            assert!(matches!(
+                gpu.draw(false),
+                Err(GpuError::ConstantBufferType { slot: 4, .. })
+            ));
+            assert!(matches!(
+                gpu.set_constant_buffer(32, Arc::new(Vec4::ZERO)),
+                Err(GpuError::InvalidResource(_))
+            ));
+
+            // a slot the shaders do not declare takes anything
+            gpu.set_constant_buffer(7, Arc::new(Vec4::ZERO)).unwrap();
+            gpu.set_constant_buffer(4, Arc::new(Sampler::default()))
+                .unwrap();
+            // the rejected draws left nothing behind
+            draw_fullscreen_quad(gpu, 0.0, Color::WHITE);
+        });
+        assert_all_pixels(&render_target, Color::WHITE);
+
+        // buffers bound before the shaders are checked by the draw
+        let bus = Arc::new(Mutex::new(Bus::new()));
+        let exit_condvar = Arc::new((Mutex::new(0), Condvar::new()));
+        let render_complete_condvar = Arc::new((Mutex::new(0), Condvar::new()));
+        let mut gpu = Gpu::new(
+            &bus,
+            &exit_condvar,
+            &render_complete_condvar,
+            Arc::new(Mutex::new(false)),
+        );
+        gpu.set_constant_buffer(0, Arc::new(Mat4x4::identity()))
+            .unwrap();
+        let pipeline_state = create_pipeline_state(&mut gpu, custom_pipeline_state());
+        gpu.set_pipeline(pipeline_state).unwrap();
+        let render_target: RenderTargetId = next_handle();
+        gpu.create_render_target(
+            render_target,
+            TARGET_SIZE,
+            TARGET_SIZE,
+            1,
+            PixelFormat::Rgba8,
+        )
+        .unwrap();
+        gpu.set_render_target(render_target).unwrap();
+        set_texture(&mut gpu, Texture::new_with_data(1, 1, vec![Color::WHITE]));
+        let triangle = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0)]
+            .map(|(x, y)| create_vertex(x, y, 1.0, 0.0, 0.0))
+            .to_vec();
+        set_mesh(&mut gpu, &Mesh::new_with_data(triangle, vec![]));
+        let expected = std::any::type_name::<Transforms>();
+        assert_eq!(
+            gpu.draw(false),
+            Err(GpuError::ConstantBufferType { slot: 0, expected })
+        );
+    }
+
+    // counts how often the Gpu hands the constant buffers to the shader
+    struct CountingVertexShader {
+        inner: CustomVertexShader,
+        init_cnt: Arc<AtomicUsize>,
+    }
+
+    impl VertexShader<VertexShaderInput, VertexShaderOutput> for CountingVertexShader {
+        fn handle(&self, vertex: &VertexShaderInput) -> VertexShaderOutput {
+            self.inner.handle(vertex)
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            self.inner.constant_buffer_layout()
+        }
+
+        fn init_constant_buffer(&mut self, buffer: &ConstantBuffers) {
+            self.init_cnt.fetch_add(1, Ordering::Relaxed);
+            self.inner.init_constant_buffer(buffer);
+        }
+    }
+
+    #[test]
+    fn test_constant_buffers_are_initialised_once_per_change() {
+        let init_cnt = Arc::new(AtomicUsize::new(0));
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let vertex_shader = CountingVertexShader {
+                inner: CustomVertexShader::new(),
+                init_cnt: Arc::clone(&init_cnt),
+            };
+            let pipeline_state =
+                PipelineState::new(Box::new(vertex_shader), Box::new(CustomPixelShader::new()));
+            let pipeline_state = create_pipeline_state(gpu, pipeline_state);
+            gpu.set_pipeline(pipeline_state).unwrap();
+            let triangle = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0)]
+                .map(|(x, y)| create_vertex(x, y, 1.0, 0.0, 0.0))
+                .to_vec();
+            set_mesh(gpu, &Mesh::new_with_data(triangle, vec![]));
+            gpu.draw(false).unwrap();
+            gpu.draw(false).unwrap();
+            assert_eq!(init_cnt.load(Ordering::Relaxed), 1);
+
+            let identity = Mat4x4::identity();
+            gpu.set_constant_buffer(0, Arc::new(Transforms::new(identity, identity, identity)))
+                .unwrap();
+            gpu.draw(false).unwrap();
+            gpu.draw(false).unwrap();
+            assert_eq!(init_cnt.load(Ordering::Relaxed), 2);
+
+            // so does binding another texture
+            draw_fullscreen_quad(gpu, 0.0, Color::WHITE);
+            assert_eq!(init_cnt.load(Ordering::Relaxed), 3);
+        });
+        assert_all_pixels(&render_target, Color::WHITE);
+    }
+
+    #[test]
+    fn test_pipeline_states_switch_within_a_frame() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let red_only = BlendState::opaque().with_write_mask(ColorWriteMask::RED);
+            let masked = custom_pipeline_state().with_blend_state(red_only);
+            let masked = create_pipeline_state(gpu, masked);
+            let opaque = custom_pipeline_state().with_blend_state(BlendState::opaque());
+            let opaque = create_pipeline_state(gpu, opaque);
+
+            gpu.set_pipeline(masked).unwrap();
+            draw_quad(gpu, -1.0, 0.0, 0.0, Color::WHITE);
+            gpu.set_pipeline(opaque).unwrap();
+            draw_quad(gpu, 0.0, 1.0, 0.0, Color::WHITE);
+
+            // destroying the bound state leaves nothing to draw with
+            gpu.destroy_pipeline_state(opaque).unwrap();
+            assert!(matches!(
+                gpu.set_pipeline(opaque),
+                Err(GpuError::StaleHandle(_))
+            ));
+            assert_eq!(gpu.draw(true), Err(GpuError::NotBound("pipeline state")));
+        });
+
+        let half = TARGET_SIZE / 2;
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let expected = if i < half {
+                    Color::new_rgba(255, 0, 0, 255)
+                } else {
+                    Color::WHITE
+                };
+                assert_eq!(
+                    *render_target.get_pixel(i, j),
+                    expected,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+            }
+        }
+    }
+
+    // a 2D vertex of a red UI quad, drawn on the same Gpu as the meshes
+    #[derive(Clone, Copy, Debug)]
+    struct UiVertex {
+        position: Vec2,
+    }
+
+    struct UiVertexShader;
+
+    impl VertexShader<UiVertex, VertexShaderOutput> for UiVertexShader {
+        fn handle(&self, vertex: &UiVertex) -> VertexShaderOutput {
+            let position = Vec4::new(vertex.position.x, vertex.position.y, 0.0, 1.0);
+            VertexShaderOutput::new(position, position, Vec4::ZERO, Vec2::ZERO, Vec3::ZERO)
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            vec![]
+        }
+
+        fn init_constant_buffer(&mut self, _buffer: &ConstantBuffers) {}
+    }
+
+    struct UiPixelShader;
+
+    impl PixelShader<VertexShaderOutput> for UiPixelShader {
+        fn handle(
+            &self,
+            _pixel_fragment: &VertexShaderOutput,
+            _pixel_quad: &PixelQuad<VertexShaderOutput>,
+        ) -> Option<PixelOutput> {
+            Some(Vec4::new(255.0, 0.0, 0.0, 255.0).into())
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            vec![]
+        }
+
+        fn init_constant_buffer(&mut self, _buffer: &ConstantBuffers) {}
+    }
+
+    #[test]
+    fn test_vertex_formats_are_picked_per_pipeline_state() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            draw_quad(gpu, -1.0, 0.0, 0.0, Color::WHITE);
+
+            let ui = PipelineState::new(Box::new(UiVertexShader), Box::new(UiPixelShader));
+            let ui = create_pipeline_state(gpu, ui);
+            gpu.set_pipeline(ui).unwrap();
+            // the mesh vertices are still bound, they do not fit the UI pipeline state
+            assert!(matches!(gpu.draw(true), Err(GpuError::InvalidResource(_))));
+
+            let quad = [(0.0, -1.0), (1.0, -1.0), (1.0, 1.0), (0.0, 1.0)].map(|(x, y)| UiVertex {
+                position: Vec2::new(x, y),
+            });
+            let vertex_buffer: BufferId = next_handle();
+            gpu.create_buffer(vertex_buffer, BufferData::vertex(Arc::from(quad)))
+                .unwrap();
+            gpu.set_vertex_buffer(vertex_buffer).unwrap();
+            gpu.draw(true).unwrap();
+
+            // a vertex buffer keeps its vertex type
+            let meshes: Arc<[VertexShaderInput]> = Arc::from(vec![]);
+            assert!(matches!(
+                gpu.update_buffer(vertex_buffer, BufferData::vertex(meshes)),
+                Err(GpuError::InvalidResource(_))
+            ));
+        });
+
+        let half = TARGET_SIZE / 2;
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let expected = if i < half {
+                    Color::WHITE
+                } else {
+                    Color::new_rgba(255, 0, 0, 255)
+                };
+                assert_eq!(
+                    *render_target.get_pixel(i, j),
+                    expected,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+            }
+        }
+    }
+
+    // fills the UI quad with the color bound to slot 0
+    struct TintPixelShader {
+        color: Vec4,
+    }
+
+    impl PixelShader<VertexShaderOutput> for TintPixelShader {
+        fn handle(
+            &self,
+            _pixel_fragment: &VertexShaderOutput,
+            _pixel_quad: &PixelQuad<VertexShaderOutput>,
+        ) -> Option<PixelOutput> {
+            Some(self.color.into())
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            vec![ConstantBufferBinding::required::<Vec4>(0)]
+        }
+
+        fn init_constant_buffer(&mut self, buffer: &ConstantBuffers) {
+            self.color = *buffer.get::<Vec4>(0).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_constant_buffers_are_bound_before_the_pipeline_state() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let tint = TintPixelShader { color: Vec4::ZERO };
+            let tint = PipelineState::new(Box::new(UiVertexShader), Box::new(tint));
+            let tint = create_pipeline_state(gpu, tint);
+
+            // slot 0 holds the transforms of the bound pipeline state, the tint replaces
+            // them ahead of the switch
+            gpu.set_constant_buffer(0, Arc::new(Vec4::new(0.0, 255.0, 0.0, 255.0)))
+                .unwrap();
+            gpu.set_pipeline(tint).unwrap();
+            let quad =
+                [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| UiVertex {
+                    position: Vec2::new(x, y),
+                });
+            let vertex_buffer: BufferId = next_handle();
+            gpu.create_buffer(vertex_buffer, BufferData::vertex(Arc::from(quad)))
+                .unwrap();
+            gpu.set_vertex_buffer(vertex_buffer).unwrap();
+            let index_buffer: BufferId = next_handle();
+            gpu.create_buffer(
+                index_buffer,
+                BufferData::Index(IndexBuffer::new_compact(&[0, 1, 2, 0, 2, 3])),
+            )
+            .unwrap();
+            gpu.set_index_buffer(index_buffer).unwrap();
+            gpu.draw(true).unwrap();
+        });
+        assert_all_pixels(&render_target, Color::new_rgba(0, 255, 0, 255));
+    }
+
+    // the color of a low-poly look, one per primitive
+    #[derive(Clone, Copy, Varyings)]
+    struct FlatVaryings {
+        #[position]
+        position: Vec4,
+        #[flat]
+        color: Vec3,
+    }
+
+    struct FlatVertexShader;
+
+    impl VertexShader<VertexShaderInput, FlatVaryings> for FlatVertexShader {
+        fn handle(&self, vertex: &VertexShaderInput) -> FlatVaryings {
+            FlatVaryings {
+                position: vertex.position,
+                color: vertex.color,
+            }
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            vec![]
+        }
+
+        fn init_constant_buffer(&mut self, _buffer: &ConstantBuffers) {}
+    }
+
+    struct FlatPixelShader;
+
+    impl PixelShader<FlatVaryings> for FlatPixelShader {
+        fn handle(
+            &self,
+            pixel_fragment: &FlatVaryings,
+            _pixel_quad: &PixelQuad<FlatVaryings>,
+        ) -> Option<PixelOutput> {
+            let color = pixel_fragment.color;
+            Some(Vec4::new(color.x, color.y, color.z, 255.0).into())
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            vec![]
+        }
+
+        fn init_constant_buffer(&mut self, _buffer: &ConstantBuffers) {}
+    }
+
+    #[test]
+    fn test_flat_varyings_take_the_provoking_vertex() {
+        let (red, green, blue) = (
+            Color::new_rgba(255, 0, 0, 255),
+            Color::new_rgba(0, 255, 0, 255),
+            Color::new_rgba(0, 0, 255, 255),
+        );
+        let render_strip = |provoking_vertex: ProvokingVertex| {
+            let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+            with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+                let rasterizer_state =
+                    RasterizerState::default().with_provoking_vertex(provoking_vertex);
+                let flat =
+                    PipelineState::new(Box::new(FlatVertexShader), Box::new(FlatPixelShader))
+                        .with_primitive_topology(PrimitiveTopology::TriangleStrip)
+                        .with_rasterizer_state(rasterizer_state);
+                let flat = create_pipeline_state(gpu, flat);
+                gpu.set_pipeline(flat).unwrap();
+
+                let strip = [
+                    (-1.0, -1.0, Vec3::new(255.0, 0.0, 0.0)),
+                    (1.0, -1.0, Vec3::new(0.0, 255.0, 0.0)),
+                    (-1.0, 1.0, Vec3::new(0.0, 0.0, 255.0)),
+                    (1.0, 1.0, Vec3::new(255.0, 255.0, 255.0)),
+                ]
+                .map(|(x, y, color)| {
+                    VertexShaderInput::new(Vec4::new(x, y, 0.0, 1.0), color, Vec2::ZERO, Vec3::ZERO)
+                })
+                .to_vec();
+                set_mesh(gpu, &Mesh::new_with_data(strip, vec![]));
+                gpu.draw(false).unwrap();
+            })
+        };
+
+        // the left half of the middle row lies in the first triangle, the right half in the
+        // second one, whose vertices are swapped to keep the winding
+        let middle = TARGET_SIZE / 2;
+        let first = render_strip(ProvokingVertex::First);
+        assert_eq!(*first.get_pixel(2, middle), red);
+        assert_eq!(*first.get_pixel(TARGET_SIZE - 3, middle), green);
+
+        let last = render_strip(ProvokingVertex::Last);
+        assert_eq!(*last.get_pixel(2, middle), blue);
+        assert_eq!(*last.get_pixel(TARGET_SIZE - 3, middle), Color::WHITE);
+    }
+
+    // a cut-out sprite, transparent on the left half of the render target
+    struct CutoutPixelShader;
+
+    impl PixelShader<VertexShaderOutput> for CutoutPixelShader {
+        fn handle(
+            &self,
+            pixel_fragment: &VertexShaderOutput,
+            _pixel_quad: &PixelQuad<VertexShaderOutput>,
+        ) -> Option<PixelOutput> {
+            let alpha = if pixel_fragment.window_pos.x < (TARGET_SIZE / 2) as f32 {
+                0.0
+            } else {
+                255.0
+            };
+            (alpha > 127.0).then_some(Vec4::new(0.0, 255.0, 0.0, alpha).into())
+        }
+
+        fn discards(&self) -> bool {
+            true
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            vec![]
+        }
+
+        fn init_constant_buffer(&mut self, _buffer: &ConstantBuffers) {}
+    }
+
+    #[test]
+    fn test_discarded_pixels_write_nothing() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let render_target = with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let mark = StencilFaceState::new(
+                CompareFunc::Always,
+                StencilOp::Keep,
+                StencilOp::Keep,
+                StencilOp::Replace,
+            );
+            let depth_stencil_state = DepthStencilState::default().with_stencil(1, mark, mark);
+            let cutout = PipelineState::new(
+                Box::new(CustomVertexShader::new()),
+                Box::new(CutoutPixelShader),
+            )
+            .with_depth_stencil_state(depth_stencil_state);
+            let cutout = create_pipeline_state(gpu, cutout);
+            gpu.set_pipeline(cutout).unwrap();
+            draw_fullscreen_quad(gpu, -0.5, Color::WHITE);
+        });
+
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let drawn = i >= TARGET_SIZE / 2;
+                let (color, stencil) = if drawn {
+                    (Color::new_rgba(0, 255, 0, 255), 1)
+                } else {
+                    (Color::BLACK, 0)
+                };
+                assert_eq!(
+                    *render_target.get_pixel(i, j),
+                    color,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+                assert_eq!(render_target.stencil(i, j), stencil, "pixel ({}, {})", i, j);
+                assert_eq!(
+                    render_target.depth(i, j) < 1.0,
+                    drawn,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+            }
+        }
+    }
+
+    // writes the albedo and the normal of a deferred shading G-buffer in one pass
+    struct GBufferPixelShader;
+
+    impl PixelShader<VertexShaderOutput> for GBufferPixelShader {
+        fn handle(
+            &self,
+            _pixel_fragment: &VertexShaderOutput,
+            _pixel_quad: &PixelQuad<VertexShaderOutput>,
+        ) -> Option<PixelOutput> {
+            let albedo = Vec4::new(200.0, 100.0, 50.0, 255.0);
+            let normal = Vec4::new(128.0, 128.0, 255.0, 255.0);
+            Some(
+                PixelOutput::new()
+                    .with_color(0, albedo)
+                    .with_color(1, normal),
+            )
+        }
+
+        fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+            vec![]
+        }
+
+        fn init_constant_buffer(&mut self, _buffer: &ConstantBuffers) {}
+    }
+
+    #[test]
+    fn test_multiple_render_targets() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let (mut albedo, mut normal, mut depth) = (None, None, None);
+        with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let [albedo_id, normal_id, depth_id] = [0; 3].map(|_| {
+                let id: RenderTargetId = next_handle();
+                gpu.create_render_target(id, TARGET_SIZE, TARGET_SIZE, 1, PixelFormat::Rgba8)
+                    .unwrap();
+                id
+            });
+            gpu.set_render_targets(&[albedo_id, normal_id], Some(depth_id))
+                .unwrap();
+            gpu.clear(
+                ClearFlags::COLOR | ClearFlags::DEPTH,
+                &Vec4::new(0.0, 0.0, 0.0, 255.0),
+                1.0,
+                0,
+            )
+            .unwrap();
+
+            // only the red channel of the normal attachment is written
+            let masked = BlendState::opaque().with_write_mask(ColorWriteMask::RED);
+            let g_buffer = PipelineState::new(
+                Box::new(CustomVertexShader::new()),
+                Box::new(GBufferPixelShader),
+            )
+            .with_attachment_blend_state(1, masked)
+            .unwrap();
+            let g_buffer = create_pipeline_state(gpu, g_buffer);
+            gpu.set_pipeline(g_buffer).unwrap();
+            draw_quad(gpu, -1.0, 0.0, 0.0, Color::WHITE);
+
+            albedo = Some(gpu.read_render_target(albedo_id).unwrap());
+            normal = Some(gpu.read_render_target(normal_id).unwrap());
+            depth = Some(gpu.read_render_target(depth_id).unwrap());
+        });
+        let (albedo, normal, depth) = (albedo.unwrap(), normal.unwrap(), depth.unwrap());
+
+        let half = TARGET_SIZE / 2;
+        for j in 0..TARGET_SIZE {
+            for i in 0..TARGET_SIZE {
+                let drawn = i < half;
+                let (albedo_color, normal_color) = if drawn {
+                    (
+                        Color::new_rgba(200, 100, 50, 255),
+                        Color::new_rgba(128, 0, 0, 255),
+                    )
+                } else {
+                    (Color::BLACK, Color::BLACK)
+                };
+                assert_eq!(
+                    *albedo.get_pixel(i, j),
+                    albedo_color,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+                assert_eq!(
+                    *normal.get_pixel(i, j),
+                    normal_color,
+                    "pixel ({}, {})",
+                    i,
+                    j
+                );
+                assert_eq!(depth.depth(i, j) < 1.0, drawn, "pixel ({}, {})", i, j);
+                // the color plane of the depth attachment is left alone
+                assert_eq!(*depth.get_pixel(i, j), Color::BLUE, "pixel ({}, {})", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_targets_are_checked_when_bound() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            let create = |gpu: &mut Gpu, width: u32, sample_cnt: u32| {
+                let id: RenderTargetId = next_handle();
+                gpu.create_render_target(id, width, TARGET_SIZE, sample_cnt, PixelFormat::Rgba8)
+                    .unwrap();
+                id
+            };
+            let (a, b) = (create(gpu, TARGET_SIZE, 1), create(gpu, TARGET_SIZE, 1));
+            let smaller = create(gpu, TARGET_SIZE / 2, 1);
+            let multisampled = create(gpu, TARGET_SIZE, 4);
+
+            assert_eq!(gpu.set_render_targets(&[a, b], None), Ok(()));
+            assert_eq!(gpu.set_render_targets(&[], Some(a)), Ok(()));
+            for (colors, depth) in [
+                (vec![a, smaller], None),
+                (vec![a], Some(multisampled)),
+                (vec![a, b, a], None),
+                (vec![a; 9], None),
+                (vec![], None),
+            ] {
+                assert!(matches!(
+                    gpu.set_render_targets(&colors, depth),
+                    Err(GpuError::InvalidResource(_))
+                ));
+            }
+            assert_eq!(
+                gpu.set_attachment_blend_state(8, BlendState::opaque()),
+                Err(GpuError::InvalidResource(
+                    "color attachment 8 is out of range".to_string()
+                ))
+            );
+            let pipeline_state = PipelineState::new(
+                Box::new(CustomVertexShader::new()),
+                Box::new(CustomPixelShader::new()),
+            )
+            .with_attachment_blend_state(8, BlendState::opaque());
+            assert!(matches!(pipeline_state, Err(GpuError::InvalidResource(_))));
+        });
+    }
+
+    #[test]
+    fn test_float_render_targets_accumulate_above_255() {
+        let texture = Texture::new_with_data(1, 1, vec![Color::WHITE]);
+        let mut targets = vec![];
+        with_gpu(TARGET_SIZE, TARGET_SIZE, 1, texture, |gpu| {
+            gpu.set_blend_state(BlendState::additive());
+            for format in [PixelFormat::Rgba32F, PixelFormat::R32F, PixelFormat::Rgba8] {
+                let id: RenderTargetId = next_handle();
+                gpu.create_render_target(id, TARGET_SIZE, TARGET_SIZE, 1, format)
+                    .unwrap();
+                gpu.set_render_targets(&[id], None).unwrap();
+                gpu.clear(ClearFlags::COLOR, &Vec4::ZERO, 1.0, 0).unwrap();
+                let light = Color::new_rgba(200, 100, 50, 255);
+                draw_fullscreen_quad(gpu, 0.0, light);
+                draw_fullscreen_quad(gpu, 0.0, light);
+                targets.push(gpu.read_render_target(id).unwrap());
+            }
+        });
+
+        // the float formats keep the sum, the R32F target reads 0 green and blue and opaque
+        // alpha, while RGBA8 saturates
+        let expected = [
+            Vec4::new(400.0, 200.0, 100.0, 510.0),
+            Vec4::new(400.0, 0.0, 0.0, 255.0),
+            Vec4::new(255.0, 200.0, 100.0, 255.0),
+        ];
+        for (target, expected) in targets.iter().zip(expected) {
+            for j in 0..TARGET_SIZE {
+                for i in 0..TARGET_SIZE {
+                    assert_eq!(
+                        target.get_texel(i, j, 0),
+                        expected,
+                        "{:?} pixel ({}, {})",
+                        target.format(),
+                        i,
+                        j
+                    );
+                }
+            }
+        }
+    }
+}
@@ -2,3 +2,5 @@ pub mod bus;
 pub mod unit;
 pub mod cpu;
 pub mod gpu;
+pub mod gpu_error;
+pub mod gpu_unittests;
@@ -0,0 +1,190 @@
+use crate::lps::common::math::vec4::Vec4;
+use std::ops::BitOr;
+
+/// Weight of a blend input, computed from the normalized source (pixel shader output) and
+/// destination (render target) colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendFactor {
+    Zero,
+    One,
+    SrcColor,
+    OneMinusSrcColor,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    DstColor,
+    OneMinusDstColor,
+    DstAlpha,
+    OneMinusDstAlpha,
+}
+
+impl BlendFactor {
+    fn value(&self, src: &[f32; 4], dst: &[f32; 4], channel: usize) -> f32 {
+        match self {
+            BlendFactor::Zero => 0.0,
+            BlendFactor::One => 1.0,
+            BlendFactor::SrcColor => src[channel],
+            BlendFactor::OneMinusSrcColor => 1.0 - src[channel],
+            BlendFactor::SrcAlpha => src[3],
+            BlendFactor::OneMinusSrcAlpha => 1.0 - src[3],
+            BlendFactor::DstColor => dst[channel],
+            BlendFactor::OneMinusDstColor => 1.0 - dst[channel],
+            BlendFactor::DstAlpha => dst[3],
+            BlendFactor::OneMinusDstAlpha => 1.0 - dst[3],
+        }
+    }
+}
+
+/// Combines the weighted source and destination, `Min` and `Max` ignore the factors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendOp {
+    Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorWriteMask(u8);
+
+impl ColorWriteMask {
+    pub const NONE: ColorWriteMask = ColorWriteMask(0);
+    pub const RED: ColorWriteMask = ColorWriteMask(1);
+    pub const GREEN: ColorWriteMask = ColorWriteMask(2);
+    pub const BLUE: ColorWriteMask = ColorWriteMask(4);
+    pub const ALPHA: ColorWriteMask = ColorWriteMask(8);
+    pub const ALL: ColorWriteMask = ColorWriteMask(15);
+
+    pub fn contains(&self, other: ColorWriteMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn channel(&self, channel: usize) -> bool {
+        self.0 & (1 << channel) != 0
+    }
+}
+
+impl BitOr for ColorWriteMask {
+    type Output = ColorWriteMask;
+
+    fn bitor(self, rhs: ColorWriteMask) -> ColorWriteMask {
+        ColorWriteMask(self.0 | rhs.0)
+    }
+}
+
+/// Output merger configuration, how a shaded pixel is combined with the render target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlendState {
+    pub enabled: bool,
+    pub src_color: BlendFactor,
+    pub dst_color: BlendFactor,
+    pub color_op: BlendOp,
+    pub src_alpha: BlendFactor,
+    pub dst_alpha: BlendFactor,
+    pub alpha_op: BlendOp,
+    pub write_mask: ColorWriteMask,
+}
+
+impl BlendState {
+    /// Blends color and alpha with the same factors and op.
+    pub fn new(src: BlendFactor, dst: BlendFactor, op: BlendOp) -> Self {
+        Self {
+            enabled: true,
+            src_color: src,
+            dst_color: dst,
+            color_op: op,
+            src_alpha: src,
+            dst_alpha: dst,
+            alpha_op: op,
+            write_mask: ColorWriteMask::ALL,
+        }
+    }
+
+    /// Overwrites the render target.
+    pub fn opaque() -> Self {
+        Self {
+            enabled: false,
+            ..BlendState::new(BlendFactor::One, BlendFactor::Zero, BlendOp::Add)
+        }
+    }
+
+    /// Classic "over" compositing for transparent surfaces.
+    pub fn alpha_blend() -> Self {
+        BlendState::new(
+            BlendFactor::SrcAlpha,
+            BlendFactor::OneMinusSrcAlpha,
+            BlendOp::Add,
+        )
+    }
+
+    /// Adds the source weighted by its alpha, for particles and glows.
+    pub fn additive() -> Self {
+        BlendState::new(BlendFactor::SrcAlpha, BlendFactor::One, BlendOp::Add)
+    }
+
+    pub fn with_alpha(mut self, src: BlendFactor, dst: BlendFactor, op: BlendOp) -> Self {
+        self.src_alpha = src;
+        self.dst_alpha = dst;
+        self.alpha_op = op;
+        self
+    }
+
+    pub fn with_write_mask(mut self, write_mask: ColorWriteMask) -> Self {
+        self.write_mask = write_mask;
+        self
+    }
+
+    /// Combines the pixel shader output `src` with the render target color `dst`, both
+    /// given in 0..255, and returns the color to store.
+    pub fn blend(&self, src: &Vec4, dst: &Vec4) -> Vec4 {
+        self.blend_in_range(src, dst, true)
+    }
+
+    /// Blends for a float render target, the colors are not clamped to 0..255 before or
+    /// after blending, so additive blending accumulates above 255.
+    pub fn blend_float(&self, src: &Vec4, dst: &Vec4) -> Vec4 {
+        self.blend_in_range(src, dst, false)
+    }
+
+    fn blend_in_range(&self, src: &Vec4, dst: &Vec4, clamp: bool) -> Vec4 {
+        let clamp_unit = |v: f32| if clamp { v.clamp(0.0, 1.0) } else { v };
+        let to_unit = |c: &Vec4| [c.x, c.y, c.z, c.w].map(|v| clamp_unit(v / 255.0));
+        let src_unit = to_unit(src);
+        let dst_unit = to_unit(dst);
+
+        let mut result = [dst.x, dst.y, dst.z, dst.w];
+        for (channel, value) in result.iter_mut().enumerate() {
+            if !self.write_mask.channel(channel) {
+                continue;
+            }
+            if !self.enabled {
+                *value = [src.x, src.y, src.z, src.w][channel];
+                continue;
+            }
+
+            let (src_factor, dst_factor, op) = if channel < 3 {
+                (self.src_color, self.dst_color, self.color_op)
+            } else {
+                (self.src_alpha, self.dst_alpha, self.alpha_op)
+            };
+            let s = src_unit[channel] * src_factor.value(&src_unit, &dst_unit, channel);
+            let d = dst_unit[channel] * dst_factor.value(&src_unit, &dst_unit, channel);
+            let blended = match op {
+                BlendOp::Add => s + d,
+                BlendOp::Subtract => s - d,
+                BlendOp::ReverseSubtract => d - s,
+                BlendOp::Min => src_unit[channel].min(dst_unit[channel]),
+                BlendOp::Max => src_unit[channel].max(dst_unit[channel]),
+            };
+            *value = clamp_unit(blended) * 255.0;
+        }
+
+        Vec4::new(result[0], result[1], result[2], result[3])
+    }
+}
+
+impl Default for BlendState {
+    fn default() -> Self {
+        BlendState::opaque()
+    }
+}
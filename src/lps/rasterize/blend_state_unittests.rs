@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::math::vec4::Vec4;
+    use crate::lps::rasterize::blend_state::{BlendFactor, BlendOp, BlendState, ColorWriteMask};
+
+    fn assert_near(actual: Vec4, expected: Vec4) {
+        let diff = actual - expected;
+        assert!(diff.len() < 1e-3, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_blend_ops() {
+        let src = Vec4::new(255.0, 102.0, 0.0, 51.0);
+        let dst = Vec4::new(51.0, 204.0, 255.0, 255.0);
+
+        let add = BlendState::new(BlendFactor::One, BlendFactor::One, BlendOp::Add);
+        assert_near(add.blend(&src, &dst), Vec4::new(255.0, 255.0, 255.0, 255.0));
+
+        let sub = BlendState::new(BlendFactor::One, BlendFactor::One, BlendOp::Subtract);
+        assert_near(sub.blend(&src, &dst), Vec4::new(204.0, 0.0, 0.0, 0.0));
+
+        let rev_sub = BlendState::new(BlendFactor::One, BlendFactor::One, BlendOp::ReverseSubtract);
+        assert_near(
+            rev_sub.blend(&src, &dst),
+            Vec4::new(0.0, 102.0, 255.0, 204.0),
+        );
+
+        // min and max ignore the factors
+        let min = BlendState::new(BlendFactor::Zero, BlendFactor::Zero, BlendOp::Min);
+        assert_near(min.blend(&src, &dst), Vec4::new(51.0, 102.0, 0.0, 51.0));
+
+        let max = BlendState::new(BlendFactor::Zero, BlendFactor::Zero, BlendOp::Max);
+        assert_near(max.blend(&src, &dst), Vec4::new(255.0, 204.0, 255.0, 255.0));
+    }
+
+    #[test]
+    fn test_blend_factors() {
+        let src = Vec4::new(255.0, 0.0, 0.0, 51.0);
+        let dst = Vec4::new(0.0, 0.0, 255.0, 255.0);
+
+        // 20% red over blue, the alpha channel keeps the destination
+        let over =
+            BlendState::alpha_blend().with_alpha(BlendFactor::Zero, BlendFactor::One, BlendOp::Add);
+        assert_near(over.blend(&src, &dst), Vec4::new(51.0, 0.0, 204.0, 255.0));
+
+        let modulate = BlendState::new(BlendFactor::DstColor, BlendFactor::Zero, BlendOp::Add);
+        let gray = Vec4::new(127.5, 127.5, 127.5, 255.0);
+        assert_near(
+            modulate.blend(&gray, &dst),
+            Vec4::new(0.0, 0.0, 127.5, 255.0),
+        );
+    }
+
+    #[test]
+    fn test_write_mask() {
+        let src = Vec4::new(10.0, 20.0, 30.0, 40.0);
+        let dst = Vec4::new(50.0, 60.0, 70.0, 80.0);
+
+        let mask = ColorWriteMask::RED | ColorWriteMask::ALPHA;
+        assert!(mask.contains(ColorWriteMask::ALPHA));
+        assert!(!mask.contains(ColorWriteMask::RED | ColorWriteMask::GREEN));
+
+        let opaque = BlendState::opaque().with_write_mask(mask);
+        assert_near(opaque.blend(&src, &dst), Vec4::new(10.0, 60.0, 70.0, 40.0));
+
+        let none = BlendState::additive().with_write_mask(ColorWriteMask::NONE);
+        assert_near(none.blend(&src, &dst), dst);
+    }
+}
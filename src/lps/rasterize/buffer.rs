@@ -0,0 +1,68 @@
+use crate::lps::rasterize::resource::BufferId;
+use std::any::Any;
+use std::sync::Arc;
+
+/// The vertex and index buffer of an uploaded mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshBuffers {
+    pub vertex_buffer: BufferId,
+    pub index_buffer: BufferId,
+}
+
+/// Indices of a draw, stored as u16 or u32. Cloning shares the data.
+#[derive(Clone, Debug)]
+pub enum IndexBuffer {
+    U16(Arc<[u16]>),
+    U32(Arc<[u32]>),
+}
+
+impl IndexBuffer {
+    /// Stores the indices as u16 if all of them fit, as u32 otherwise.
+    pub fn new_compact(indices: &[u32]) -> Self {
+        if indices.iter().all(|&index| index <= u16::MAX as u32) {
+            IndexBuffer::U16(indices.iter().map(|&index| index as u16).collect())
+        } else {
+            IndexBuffer::U32(Arc::from(indices))
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            IndexBuffer::U16(indices) => indices.len(),
+            IndexBuffer::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, i: usize) -> usize {
+        match self {
+            IndexBuffer::U16(indices) => indices[i] as usize,
+            IndexBuffer::U32(indices) => indices[i] as usize,
+        }
+    }
+
+    /// The largest index, `None` for an empty buffer.
+    pub fn max(&self) -> Option<usize> {
+        match self {
+            IndexBuffer::U16(indices) => indices.iter().max().map(|&index| index as usize),
+            IndexBuffer::U32(indices) => indices.iter().max().map(|&index| index as usize),
+        }
+    }
+}
+
+/// Contents of a buffer. `Vertex` holds an `Arc<[V]>` of any vertex type, it is type checked
+/// against the bound pipeline state when it is drawn.
+#[derive(Clone)]
+pub enum BufferData {
+    Vertex(Arc<dyn Any + Send + Sync>),
+    Index(IndexBuffer),
+}
+
+impl BufferData {
+    pub fn vertex<V: Send + Sync + 'static>(vertices: Arc<[V]>) -> Self {
+        BufferData::Vertex(Arc::new(vertices))
+    }
+}
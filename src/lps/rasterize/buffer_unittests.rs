@@ -0,0 +1,18 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::rasterize::buffer::IndexBuffer;
+
+    #[test]
+    fn test_index_buffer_compaction() {
+        let small = IndexBuffer::new_compact(&[0, 1, 65535]);
+        assert!(matches!(small, IndexBuffer::U16(_)));
+        assert_eq!(small.len(), 3);
+        assert_eq!(small.get(2), 65535);
+
+        let large = IndexBuffer::new_compact(&[0, 65536]);
+        assert!(matches!(large, IndexBuffer::U32(_)));
+        assert_eq!(large.get(1), 65536);
+
+        assert!(IndexBuffer::new_compact(&[]).is_empty());
+    }
+}
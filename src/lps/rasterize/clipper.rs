@@ -0,0 +1,134 @@
+use crate::lps::common::math::vec4::Vec4;
+use crate::lps::rasterize::vt_output::{LerpFactor, VertexShaderOutputPositionAndLerp};
+
+// signed distances to the six frustum planes in homogeneous clip space,
+// a vertex is inside when all of them are non-negative
+const CLIP_PLANES: [fn(&Vec4) -> f32; 6] = [
+    |p| p.w + p.z, // near
+    |p| p.w - p.z, // far
+    |p| p.w + p.x, // left
+    |p| p.w - p.x, // right
+    |p| p.w + p.y, // bottom
+    |p| p.w - p.y, // top
+];
+
+pub struct Clipper;
+
+impl Clipper {
+    /// Clips a triangle in clip space against the view frustum (Sutherland–Hodgman).
+    /// Returns the clipped polygon in the winding of the input triangle, to be drawn as
+    /// a triangle fan, or an empty list if the triangle is completely outside.
+    pub fn clip_triangle<Vertex>(v0: &Vertex, v1: &Vertex, v2: &Vertex) -> Vec<Vertex>
+    where
+        Vertex: VertexShaderOutputPositionAndLerp + Clone,
+    {
+        let vertices = [v0, v1, v2];
+
+        let mut inside_all = true;
+        for plane in CLIP_PLANES {
+            let outside_cnt = vertices
+                .iter()
+                .filter(|v| plane(v.position()) < 0.0)
+                .count();
+            if outside_cnt == 3 {
+                return vec![];
+            }
+            inside_all &= outside_cnt == 0;
+        }
+
+        if inside_all {
+            return vec![v0.clone(), v1.clone(), v2.clone()];
+        }
+
+        let mut polygon = vec![v0.clone(), v1.clone(), v2.clone()];
+        for plane in CLIP_PLANES {
+            polygon = Clipper::clip_polygon(&polygon, plane);
+            if polygon.len() < 3 {
+                return vec![];
+            }
+        }
+
+        polygon
+    }
+
+    /// Clips a line in clip space against the view frustum. Returns the visible part in
+    /// the direction of the input line, or `None` if the line is completely outside.
+    pub fn clip_line<Vertex>(v0: &Vertex, v1: &Vertex) -> Option<[Vertex; 2]>
+    where
+        Vertex: VertexShaderOutputPositionAndLerp + Clone,
+    {
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        for plane in CLIP_PLANES {
+            let d0 = plane(v0.position());
+            let d1 = plane(v1.position());
+            if d0 < 0.0 && d1 < 0.0 {
+                return None;
+            }
+            if d0 < 0.0 {
+                t0 = t0.max(d0 / (d0 - d1));
+            } else if d1 < 0.0 {
+                t1 = t1.min(d0 / (d0 - d1));
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+        if t0 == 0.0 && t1 == 1.0 {
+            return Some([v0.clone(), v1.clone()]);
+        }
+        Some([clip_lerp(v0, v1, t0), clip_lerp(v0, v1, t1)])
+    }
+
+    /// A point is drawn if its position is inside the view frustum.
+    pub fn clip_point<Vertex>(v: &Vertex) -> bool
+    where
+        Vertex: VertexShaderOutputPositionAndLerp,
+    {
+        CLIP_PLANES.iter().all(|plane| plane(v.position()) >= 0.0)
+    }
+
+    fn clip_polygon<Vertex>(polygon: &[Vertex], plane: fn(&Vec4) -> f32) -> Vec<Vertex>
+    where
+        Vertex: VertexShaderOutputPositionAndLerp + Clone,
+    {
+        let mut result = Vec::with_capacity(polygon.len() + 1);
+
+        for i in 0..polygon.len() {
+            let curr = &polygon[i];
+            let next = &polygon[(i + 1) % polygon.len()];
+            let curr_dist = plane(curr.position());
+            let next_dist = plane(next.position());
+
+            if curr_dist >= 0.0 {
+                result.push(curr.clone());
+            }
+
+            // the edge crosses the plane
+            if (curr_dist >= 0.0) != (next_dist >= 0.0) {
+                let factor = curr_dist / (curr_dist - next_dist);
+                result.push(clip_lerp(curr, next, factor));
+            }
+        }
+
+        result
+    }
+}
+
+// attributes and the position are linear in clip space, `noperspective` attributes are
+// taken where the crossing ends up on the screen space edge after the perspective division
+fn clip_lerp<Vertex>(v0: &Vertex, v1: &Vertex, t: f32) -> Vertex
+where
+    Vertex: VertexShaderOutputPositionAndLerp,
+{
+    let (pos0, pos1) = (*v0.position(), *v1.position());
+    let w = pos0.w + (pos1.w - pos0.w) * t;
+    let linear = if w.abs() < f32::EPSILON {
+        t
+    } else {
+        t * pos1.w / w
+    };
+    let mut vertex = Vertex::lerp(v0, v1, LerpFactor::new(t, linear));
+    *vertex.position_as_mut() = Vec4::lerp(pos0, pos1, t);
+    vertex
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::math::vec2::Vec2;
+    use crate::lps::common::math::vec3::Vec3;
+    use crate::lps::common::math::vec4::Vec4;
+    use crate::lps::rasterize::clipper::Clipper;
+    use crate::lps::rasterize::vt_output::{VertexShaderOutput, VertexShaderOutputPositionAndLerp};
+
+    // the texcoord mirrors clip space x and w, so it shows whether attributes were lerped
+    fn create_vertex(x: f32, y: f32, z: f32, w: f32) -> VertexShaderOutput {
+        VertexShaderOutput::new(
+            Vec4::ZERO,
+            Vec4::new(x, y, z, w),
+            Vec4::ZERO,
+            Vec2::new(x, w),
+            Vec3::ZERO,
+        )
+    }
+
+    #[test]
+    fn test_clip_triangle_inside() {
+        let v0 = create_vertex(-0.5, -0.5, 0.0, 1.0);
+        let v1 = create_vertex(0.5, -0.5, 0.0, 1.0);
+        let v2 = create_vertex(0.0, 0.5, 0.0, 1.0);
+
+        let polygon = Clipper::clip_triangle(&v0, &v1, &v2);
+        assert_eq!(polygon.len(), 3);
+        assert!(*polygon[1].position() == *v1.position());
+    }
+
+    #[test]
+    fn test_clip_triangle_outside() {
+        let v0 = create_vertex(2.0, -0.5, 0.0, 1.0);
+        let v1 = create_vertex(3.0, -0.5, 0.0, 1.0);
+        let v2 = create_vertex(2.5, 0.5, 0.0, 1.0);
+
+        assert!(Clipper::clip_triangle(&v0, &v1, &v2).is_empty());
+    }
+
+    #[test]
+    fn test_clip_triangle_behind_camera() {
+        // v2 is behind the camera, so the triangle turns into a quad at the near plane
+        let v0 = create_vertex(-0.5, -0.5, 0.0, 1.0);
+        let v1 = create_vertex(0.5, -0.5, 0.0, 1.0);
+        let v2 = create_vertex(0.0, 0.5, -3.0, -1.0);
+
+        let polygon = Clipper::clip_triangle(&v0, &v1, &v2);
+        assert_eq!(polygon.len(), 4);
+        for vertex in &polygon {
+            let p = vertex.position();
+            assert!(p.w > 0.0);
+            assert!(p.z >= -p.w - 1e-5 && p.z <= p.w + 1e-5);
+            assert!(p.x.abs() <= p.w + 1e-5 && p.y.abs() <= p.w + 1e-5);
+            assert!((vertex.texcoord.x - p.x).abs() < 1e-5);
+            assert!((vertex.texcoord.y - p.w).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_clip_line() {
+        // crosses the right plane at x = 1 and ends behind the camera
+        let v0 = create_vertex(0.0, 0.0, 0.0, 1.0);
+        let v1 = create_vertex(3.0, 0.0, 0.0, 1.0);
+        let [c0, c1] = Clipper::clip_line(&v0, &v1).unwrap();
+        assert!(*c0.position() == *v0.position());
+        assert!((c1.position().x - 1.0).abs() < 1e-5);
+        assert!((c1.texcoord.x - 1.0).abs() < 1e-5);
+
+        let v2 = create_vertex(0.0, 0.0, -3.0, -1.0);
+        let [_, c2] = Clipper::clip_line(&v0, &v2).unwrap();
+        assert!((c2.position().z + c2.position().w).abs() < 1e-5);
+
+        let v3 = create_vertex(2.0, 0.0, 0.0, 1.0);
+        assert!(Clipper::clip_line(&v1, &v3).is_none());
+        assert!(Clipper::clip_point(&v0));
+        assert!(!Clipper::clip_point(&v3));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::lps::common::math::mat4x4::Mat4x4;
+use crate::lps::common::math::vec4::Vec4;
+use crate::lps::common::sampler::Sampler;
+use crate::lps::core::gpu_error::GpuError;
+use std::any::{type_name, Any, TypeId};
+use std::sync::Arc;
+
+/// A value that can be bound to a constant buffer slot, usually a `#[repr(C)]` struct of
+/// the uniforms of a shader. It is copied into the command that binds it.
+pub trait ConstantBuffer: Copy + Send + Sync + 'static {}
+
+impl ConstantBuffer for Mat4x4 {}
+
+impl ConstantBuffer for Vec4 {}
+
+impl ConstantBuffer for Sampler {}
+
+/// The values bound to the constant buffer slots when a draw starts. Shaders copy what
+/// they need from it once, and read their copies while the draw runs on many threads.
+#[derive(Clone, Default)]
+pub struct ConstantBuffers {
+    slots: Vec<Option<Arc<dyn Any + Send + Sync>>>,
+}
+
+impl ConstantBuffers {
+    pub fn new(slots: Vec<Option<Arc<dyn Any + Send + Sync>>>) -> Self {
+        ConstantBuffers { slots }
+    }
+
+    /// The value in `slot` if it is a `T`.
+    pub fn get<T: Any>(&self, slot: usize) -> Option<&T> {
+        self.get_any(slot)
+            .and_then(|buffer| buffer.downcast_ref::<T>())
+    }
+
+    pub fn get_any(&self, slot: usize) -> Option<&(dyn Any + Send + Sync)> {
+        self.slots.get(slot).and_then(|buffer| buffer.as_deref())
+    }
+}
+
+/// A constant buffer slot a shader reads and the type it expects there. Shaders declare
+/// their slots, so a wrong bind is reported by the Gpu instead of failing a downcast
+/// inside the shader.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantBufferBinding {
+    pub slot: usize,
+    pub required: bool,
+    type_id: TypeId,
+    type_name: &'static str,
+}
+
+impl ConstantBufferBinding {
+    /// The draw fails if nothing is bound to `slot`.
+    pub fn required<T: Any>(slot: usize) -> Self {
+        Self {
+            slot,
+            required: true,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+        }
+    }
+
+    /// The shader falls back to a default if nothing is bound to `slot`.
+    pub fn optional<T: Any>(slot: usize) -> Self {
+        Self {
+            required: false,
+            ..Self::required::<T>(slot)
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Checks the value bound to the slot, `None` if there is none.
+    pub fn validate(&self, buffer: Option<&(dyn Any + Send + Sync)>) -> Result<(), GpuError> {
+        match buffer {
+            Some(buffer) if buffer.type_id() != self.type_id => Err(GpuError::ConstantBufferType {
+                slot: self.slot,
+                expected: self.type_name,
+            }),
+            None if self.required => Err(GpuError::ConstantBufferMissing {
+                slot: self.slot,
+                expected: self.type_name,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::math::mat4x4::Mat4x4;
+    use crate::lps::common::math::vec4::Vec4;
+    use crate::lps::common::sampler::Sampler;
+    use crate::lps::core::gpu_error::GpuError;
+    use crate::lps::rasterize::constant_buffer::ConstantBufferBinding;
+
+    #[test]
+    fn test_binding_validation() {
+        let matrix = ConstantBufferBinding::required::<Mat4x4>(2);
+        assert_eq!(matrix.validate(Some(&Mat4x4::identity())), Ok(()));
+        assert_eq!(
+            matrix.validate(Some(&Vec4::ZERO)),
+            Err(GpuError::ConstantBufferType {
+                slot: 2,
+                expected: matrix.type_name()
+            })
+        );
+        assert_eq!(
+            matrix.validate(None).unwrap_err().to_string(),
+            format!(
+                "constant buffer 2 expects a {}, but none is bound",
+                matrix.type_name()
+            )
+        );
+
+        let sampler = ConstantBufferBinding::optional::<Sampler>(4);
+        assert_eq!(sampler.validate(None), Ok(()));
+        assert!(sampler.validate(Some(&Mat4x4::identity())).is_err());
+    }
+}
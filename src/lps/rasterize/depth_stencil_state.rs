@@ -0,0 +1,199 @@
+/// Comparison between an incoming value and the value stored in the render target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompareFunc {
+    Never,
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
+}
+
+impl CompareFunc {
+    /// Tells whether `value` passes against the `stored` one.
+    pub fn compare<T: PartialOrd>(&self, value: T, stored: T) -> bool {
+        match self {
+            CompareFunc::Never => false,
+            CompareFunc::Less => value < stored,
+            CompareFunc::Equal => value == stored,
+            CompareFunc::LessEqual => value <= stored,
+            CompareFunc::Greater => value > stored,
+            CompareFunc::NotEqual => value != stored,
+            CompareFunc::GreaterEqual => value >= stored,
+            CompareFunc::Always => true,
+        }
+    }
+}
+
+/// Update of a stencil value, `Incr` and `Decr` saturate, the `Wrap` variants wrap around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    Incr,
+    Decr,
+    IncrWrap,
+    DecrWrap,
+    Invert,
+}
+
+impl StencilOp {
+    pub fn apply(&self, stored: u8, reference: u8) -> u8 {
+        match self {
+            StencilOp::Keep => stored,
+            StencilOp::Zero => 0,
+            StencilOp::Replace => reference,
+            StencilOp::Incr => stored.saturating_add(1),
+            StencilOp::Decr => stored.saturating_sub(1),
+            StencilOp::IncrWrap => stored.wrapping_add(1),
+            StencilOp::DecrWrap => stored.wrapping_sub(1),
+            StencilOp::Invert => !stored,
+        }
+    }
+}
+
+/// Stencil test and updates for the triangles of one facing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StencilFaceState {
+    pub func: CompareFunc,
+    /// Applied when the stencil test fails.
+    pub fail_op: StencilOp,
+    /// Applied when the stencil test passes and the depth test fails.
+    pub depth_fail_op: StencilOp,
+    /// Applied when both tests pass.
+    pub pass_op: StencilOp,
+}
+
+impl StencilFaceState {
+    pub fn new(
+        func: CompareFunc,
+        fail_op: StencilOp,
+        depth_fail_op: StencilOp,
+        pass_op: StencilOp,
+    ) -> Self {
+        Self {
+            func,
+            fail_op,
+            depth_fail_op,
+            pass_op,
+        }
+    }
+}
+
+impl Default for StencilFaceState {
+    fn default() -> Self {
+        StencilFaceState::new(
+            CompareFunc::Always,
+            StencilOp::Keep,
+            StencilOp::Keep,
+            StencilOp::Keep,
+        )
+    }
+}
+
+/// Per-pixel depth and stencil test configuration. With the depth test disabled every pixel
+/// passes it and the depth buffer is left untouched, the same goes for the stencil test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthStencilState {
+    pub depth_enable: bool,
+    pub depth_write: bool,
+    pub depth_func: CompareFunc,
+    pub stencil_enable: bool,
+    pub stencil_ref: u8,
+    pub stencil_read_mask: u8,
+    pub stencil_write_mask: u8,
+    pub front_face: StencilFaceState,
+    pub back_face: StencilFaceState,
+}
+
+impl DepthStencilState {
+    pub fn new(depth_enable: bool, depth_write: bool, depth_func: CompareFunc) -> Self {
+        Self {
+            depth_enable,
+            depth_write,
+            depth_func,
+            stencil_enable: false,
+            stencil_ref: 0,
+            stencil_read_mask: 0xff,
+            stencil_write_mask: 0xff,
+            front_face: StencilFaceState::default(),
+            back_face: StencilFaceState::default(),
+        }
+    }
+
+    /// Enables the stencil test with separate states for front and back facing triangles.
+    pub fn with_stencil(
+        mut self,
+        stencil_ref: u8,
+        front_face: StencilFaceState,
+        back_face: StencilFaceState,
+    ) -> Self {
+        self.stencil_enable = true;
+        self.stencil_ref = stencil_ref;
+        self.front_face = front_face;
+        self.back_face = back_face;
+        self
+    }
+
+    pub fn with_stencil_masks(mut self, read_mask: u8, write_mask: u8) -> Self {
+        self.stencil_read_mask = read_mask;
+        self.stencil_write_mask = write_mask;
+        self
+    }
+
+    pub fn depth_test(&self, depth: f32, stored: f32) -> bool {
+        !self.depth_enable || self.depth_func.compare(depth, stored)
+    }
+
+    pub fn writes_depth(&self) -> bool {
+        self.depth_enable && self.depth_write
+    }
+
+    /// Compares the masked reference value against the masked `stored` one.
+    pub fn stencil_test(&self, front_facing: bool, stored: u8) -> bool {
+        if !self.stencil_enable {
+            return true;
+        }
+        let face = self.face(front_facing);
+        let mask = self.stencil_read_mask;
+        face.func.compare(self.stencil_ref & mask, stored & mask)
+    }
+
+    /// Returns the new stencil value of a pixel after its stencil and depth tests.
+    pub fn stencil_update(
+        &self,
+        front_facing: bool,
+        stored: u8,
+        stencil_pass: bool,
+        depth_pass: bool,
+    ) -> u8 {
+        if !self.stencil_enable {
+            return stored;
+        }
+        let face = self.face(front_facing);
+        let op = match (stencil_pass, depth_pass) {
+            (false, _) => face.fail_op,
+            (true, false) => face.depth_fail_op,
+            (true, true) => face.pass_op,
+        };
+        let value = op.apply(stored, self.stencil_ref);
+        (stored & !self.stencil_write_mask) | (value & self.stencil_write_mask)
+    }
+
+    fn face(&self, front_facing: bool) -> &StencilFaceState {
+        if front_facing {
+            &self.front_face
+        } else {
+            &self.back_face
+        }
+    }
+}
+
+impl Default for DepthStencilState {
+    fn default() -> Self {
+        DepthStencilState::new(true, true, CompareFunc::Less)
+    }
+}
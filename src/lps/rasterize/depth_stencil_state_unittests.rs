@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::rasterize::depth_stencil_state::{
+        CompareFunc, DepthStencilState, StencilFaceState, StencilOp,
+    };
+
+    #[test]
+    fn test_stencil_ops() {
+        assert_eq!(StencilOp::Keep.apply(7, 3), 7);
+        assert_eq!(StencilOp::Zero.apply(7, 3), 0);
+        assert_eq!(StencilOp::Replace.apply(7, 3), 3);
+        assert_eq!(StencilOp::Incr.apply(255, 3), 255);
+        assert_eq!(StencilOp::Decr.apply(0, 3), 0);
+        assert_eq!(StencilOp::IncrWrap.apply(255, 3), 0);
+        assert_eq!(StencilOp::DecrWrap.apply(0, 3), 255);
+        assert_eq!(StencilOp::Invert.apply(0b1010_0000, 3), 0b0101_1111);
+    }
+
+    #[test]
+    fn test_stencil_test_and_update() {
+        let front = StencilFaceState::new(
+            CompareFunc::Equal,
+            StencilOp::Zero,
+            StencilOp::Incr,
+            StencilOp::Replace,
+        );
+        let back = StencilFaceState::new(
+            CompareFunc::Never,
+            StencilOp::Invert,
+            StencilOp::Keep,
+            StencilOp::Keep,
+        );
+        let state = DepthStencilState::default()
+            .with_stencil(0x35, front, back)
+            .with_stencil_masks(0x0f, 0xf0);
+
+        // only the low nibble is compared, only the high nibble is written
+        assert!(state.stencil_test(true, 0xa5));
+        assert!(!state.stencil_test(true, 0xa6));
+        assert!(!state.stencil_test(false, 0xa5));
+
+        assert_eq!(state.stencil_update(true, 0xa5, true, true), 0x35);
+        assert_eq!(state.stencil_update(true, 0xa5, true, false), 0xa5);
+        assert_eq!(state.stencil_update(true, 0xa6, false, true), 0x06);
+        assert_eq!(state.stencil_update(false, 0xa5, false, true), 0x55);
+
+        let disabled = DepthStencilState::default();
+        assert!(disabled.stencil_test(false, 0x12));
+        assert_eq!(disabled.stencil_update(true, 0x12, true, true), 0x12);
+    }
+}
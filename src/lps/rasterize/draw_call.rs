@@ -0,0 +1,290 @@
+use crate::lps::common::math::mat4x4::Mat4x4;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::buffer::IndexBuffer;
+use crate::lps::rasterize::clipper::Clipper;
+use crate::lps::rasterize::pipeline::{PipeLine, PixelShader, VertexShader};
+use crate::lps::rasterize::pixel_quad::PixelQuad;
+use crate::lps::rasterize::primitive_topology::Primitive;
+use crate::lps::rasterize::rasterizer_state::FillMode;
+use crate::lps::rasterize::render_target::{Attachments, RenderTargetTile};
+use crate::lps::rasterize::render_util::RenderUtil;
+use crate::lps::rasterize::resource::BufferId;
+use crate::lps::rasterize::viewport::Viewport;
+use crate::lps::rasterize::vt_output::{BarycentricWeights, VertexShaderOutputPositionAndLerp};
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// edge length of the square tiles the render target is split into for drawing
+const TILE_SIZE: u32 = 64;
+
+/// The resources of a draw the Gpu resolved from its bindings. The pipeline state runs it
+/// with its shaders, which know the vertex types.
+pub struct DrawCall<'a> {
+    pub vertex_buffer: BufferId,
+    /// The `Arc<[V]>` of the vertex buffer.
+    pub vertices: &'a (dyn Any + Send + Sync),
+    pub indices: Option<&'a IndexBuffer>,
+    pub pipe_line: &'a PipeLine,
+    pub attachments: Attachments<'a>,
+    pub thread_cnt: usize,
+}
+
+impl DrawCall<'_> {
+    pub fn execute<VSInput, VSOutput>(
+        self,
+        vertex_shader: &(dyn VertexShader<VSInput, VSOutput> + Send + Sync),
+        pixel_shader: &(dyn PixelShader<VSOutput> + Send + Sync),
+    ) -> Result<(), GpuError>
+    where
+        VSInput: 'static + Sync + Send + Copy,
+        VSOutput: 'static + VertexShaderOutputPositionAndLerp + Sync + Send + Copy,
+    {
+        let vertex_list = self
+            .vertices
+            .downcast_ref::<Arc<[VSInput]>>()
+            .ok_or_else(|| {
+                GpuError::InvalidResource(format!(
+                    "vertex type of {:?} is not matched by the pipeline state",
+                    self.vertex_buffer
+                ))
+            })?;
+        let index_list = self.indices;
+        if let Some(max) = index_list.and_then(IndexBuffer::max) {
+            if max >= vertex_list.len() {
+                return Err(GpuError::InvalidResource(format!(
+                    "index {} is out of range for the {} vertices of {:?}",
+                    max,
+                    vertex_list.len(),
+                    self.vertex_buffer
+                )));
+            }
+        }
+        let pipe_line = self.pipe_line;
+        let attachments = self.attachments;
+        let target_size = (attachments.width(), attachments.height());
+        let viewport = pipe_line
+            .viewport()
+            .unwrap_or_else(|| Viewport::new(0.0, 0.0, target_size.0 as f32, target_size.1 as f32));
+        let viewport_mat = viewport.mat();
+        let scissor = pipe_line.scissor();
+
+        let handled_vertex_list = vertex_list
+            .iter()
+            .map(|vertex| vertex_shader.handle(vertex))
+            .collect::<Vec<VSOutput>>();
+
+        let vertex_cnt = match &index_list {
+            Some(index_list) => index_list.len(),
+            None => vertex_list.len(),
+        };
+
+        let vertex_at = |i: usize| {
+            let index = match &index_list {
+                Some(index_list) => index_list.get(i),
+                None => i,
+            };
+            &handled_vertex_list[index]
+        };
+        let rasterizer_state = *pipe_line.rasterizer_state();
+        let to_screen_space = |vertex: VSOutput| {
+            let mut vertex = to_screen_space(vertex, &viewport_mat);
+            vertex.position_as_mut().z += rasterizer_state.depth_bias;
+            vertex
+        };
+
+        let topology = pipe_line.primitive_topology();
+        // lines and points have no facing, they are drawn as front facing, while the edges
+        // and vertices of a triangle drawn in wireframe or point mode keep its facing
+        let mut screen_primitives = vec![];
+        for primitive in topology.primitives(vertex_cnt) {
+            // every vertex of the primitive takes the flat varyings of the provoking one
+            let provoking =
+                vertex_at(topology.provoking_vertex(&primitive, rasterizer_state.provoking_vertex));
+            let vertex_at = |i: usize| {
+                let mut vertex = *vertex_at(i);
+                vertex.copy_flat(provoking);
+                vertex
+            };
+            match primitive {
+                Primitive::Point(i0) => {
+                    let v0 = vertex_at(i0);
+                    if Clipper::clip_point(&v0) {
+                        screen_primitives.push((Primitive::Point(to_screen_space(v0)), true));
+                    }
+                }
+                Primitive::Line([i0, i1]) => {
+                    if let Some(line) = Clipper::clip_line(&vertex_at(i0), &vertex_at(i1)) {
+                        screen_primitives.push((Primitive::Line(line.map(to_screen_space)), true));
+                    }
+                }
+                Primitive::Triangle([i0, i1, i2]) => {
+                    let [v0, v1, v2] = [i0, i1, i2].map(vertex_at);
+                    let (v0, v1, v2) = (&v0, &v1, &v2);
+                    if rasterizer_state.is_culled(v0.position(), v1.position(), v2.position()) {
+                        continue;
+                    }
+                    let front_facing = rasterizer_state.is_front_facing(
+                        v0.position(),
+                        v1.position(),
+                        v2.position(),
+                    );
+
+                    match rasterizer_state.fill_mode {
+                        FillMode::Solid => {}
+                        FillMode::Wireframe => {
+                            for (a, b) in [(v0, v1), (v1, v2), (v2, v0)] {
+                                if let Some(line) = Clipper::clip_line(a, b) {
+                                    let line = Primitive::Line(line.map(to_screen_space));
+                                    screen_primitives.push((line, front_facing));
+                                }
+                            }
+                            continue;
+                        }
+                        FillMode::Point => {
+                            for v in [v0, v1, v2] {
+                                if Clipper::clip_point(v) {
+                                    let point = Primitive::Point(to_screen_space(*v));
+                                    screen_primitives.push((point, front_facing));
+                                }
+                            }
+                            continue;
+                        }
+                    }
+
+                    // clip in homogeneous clip space, before the perspective division
+                    let polygon = Clipper::clip_triangle(v0, v1, v2)
+                        .into_iter()
+                        .map(to_screen_space)
+                        .collect::<Vec<VSOutput>>();
+
+                    for j in 1..polygon.len().saturating_sub(1) {
+                        let triangle = [polygon[0], polygon[j], polygon[j + 1]];
+                        screen_primitives.push((Primitive::Triangle(triangle), front_facing));
+                    }
+                }
+            }
+        }
+
+        let get_colors = |v0: &VSOutput,
+                          v1: &VSOutput,
+                          v2: &VSOutput,
+                          weights: &[BarycentricWeights; 4],
+                          mask: [bool; 4]| {
+            let fragments = weights.map(|w| VSOutput::lerp_barycentric(v0, v1, v2, &w));
+            let mut colors = [None; 4];
+            for lane in 0..4 {
+                if mask[lane] {
+                    let pixel_quad = PixelQuad::new(&fragments, lane);
+                    colors[lane] = pixel_shader.handle(pixel_quad.fragment(), &pixel_quad);
+                }
+            }
+            colors
+        };
+
+        // bin the primitives into tiles, every tile draws its primitives in submission order,
+        // so the result does not depend on how the tiles are spread over the threads
+        let tiles = attachments.tiles_mut(TILE_SIZE);
+        let tiles_x = target_size.0.div_ceil(TILE_SIZE);
+        let mut bins = vec![vec![]; tiles.len()];
+        for (i, (primitive, _)) in screen_primitives.iter().enumerate() {
+            let xs = primitive.vertices().iter().map(|v| v.position().x);
+            let ys = primitive.vertices().iter().map(|v| v.position().y);
+            let to_tile = |v: f32, size: u32| (v.max(0.0) as u32).min(size - 1) / TILE_SIZE;
+            let min_x = to_tile(xs.clone().reduce(f32::min).unwrap(), target_size.0);
+            let max_x = to_tile(xs.reduce(f32::max).unwrap(), target_size.0);
+            let min_y = to_tile(ys.clone().reduce(f32::min).unwrap(), target_size.1);
+            let max_y = to_tile(ys.reduce(f32::max).unwrap(), target_size.1);
+            for ty in min_y..=max_y {
+                for tx in min_x..=max_x {
+                    bins[(ty * tiles_x + tx) as usize].push(i);
+                }
+            }
+        }
+
+        let depth_stencil_state = pipe_line.depth_stencil_state();
+        let blend_states = pipe_line.blend_states();
+        // a shader that can discard runs before the depth and stencil tests
+        let late_z = pixel_shader.discards();
+        let draw_tile = |(mut tile, bin): (RenderTargetTile, Vec<usize>)| {
+            if let Some(scissor) = &scissor {
+                tile.set_scissor(scissor);
+            }
+            for i in bin {
+                match &screen_primitives[i] {
+                    (Primitive::Point(point), front_facing) => RenderUtil::draw_point(
+                        &mut tile,
+                        point,
+                        *front_facing,
+                        depth_stencil_state,
+                        blend_states,
+                        late_z,
+                        &get_colors,
+                    ),
+                    (Primitive::Line(line), front_facing) => RenderUtil::draw_line(
+                        &mut tile,
+                        line,
+                        *front_facing,
+                        depth_stencil_state,
+                        blend_states,
+                        late_z,
+                        &get_colors,
+                    ),
+                    (Primitive::Triangle(triangle), front_facing) => RenderUtil::draw_triangle(
+                        &mut tile,
+                        triangle,
+                        *front_facing,
+                        depth_stencil_state,
+                        blend_states,
+                        late_z,
+                        &get_colors,
+                    ),
+                }
+            }
+        };
+
+        let jobs = tiles
+            .into_iter()
+            .zip(bins)
+            .filter(|(_, bin)| !bin.is_empty())
+            .collect::<Vec<_>>();
+        let thread_cnt = self.thread_cnt.min(jobs.len());
+        if thread_cnt <= 1 {
+            jobs.into_iter().for_each(draw_tile);
+        } else {
+            let jobs = Mutex::new(jobs.into_iter());
+            thread::scope(|scope| {
+                for _ in 0..thread_cnt {
+                    scope.spawn(|| loop {
+                        let job = jobs.lock().unwrap().next();
+                        match job {
+                            Some(job) => draw_tile(job),
+                            None => break,
+                        }
+                    });
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn to_screen_space<VSOutput>(mut vertex: VSOutput, viewport_mat: &Mat4x4) -> VSOutput
+where
+    VSOutput: VertexShaderOutputPositionAndLerp,
+{
+    // apply perspective division
+    let mut v = *vertex.position();
+    let rhw = 1.0 / v.w;
+    v *= rhw;
+    v.w = 1.0;
+
+    // apply viewport transform
+    v = *viewport_mat * v;
+
+    // keep 1/w of clip space for perspective-correct interpolation
+    v.w = rhw;
+    *(vertex.position_as_mut()) = v;
+    vertex
+}
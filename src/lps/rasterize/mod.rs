@@ -1,8 +1,32 @@
+pub mod blend_state;
+pub mod blend_state_unittests;
+pub mod buffer;
+pub mod buffer_unittests;
+pub mod clipper;
+pub mod clipper_unittests;
+pub mod constant_buffer;
+pub mod constant_buffer_unittests;
+pub mod depth_stencil_state;
+pub mod depth_stencil_state_unittests;
+pub mod draw_call;
 pub mod pipeline;
+pub mod pipeline_state;
+pub mod pixel_format;
+pub mod pixel_format_unittests;
+pub mod pixel_output;
+pub mod pixel_quad;
 pub mod pixel_shader;
+pub mod primitive_topology;
+pub mod primitive_topology_unittests;
+pub mod rasterizer_state;
 pub mod render_cmds;
 pub mod render_target;
 pub mod render_util;
+pub mod render_util_unittests;
+pub mod resource;
+pub mod resource_unittests;
 pub mod vertex_shader;
+pub mod viewport;
 pub mod vt_input;
 pub mod vt_output;
+pub mod vt_output_unittests;
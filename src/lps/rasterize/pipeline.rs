@@ -1,73 +1,126 @@
-use crate::lps::common::math::vec4::Vec4;
-use std::{any::Any, sync::Arc};
+use crate::lps::common::rect::Rect;
+use crate::lps::rasterize::blend_state::BlendState;
+use crate::lps::rasterize::constant_buffer::{ConstantBufferBinding, ConstantBuffers};
+use crate::lps::rasterize::depth_stencil_state::DepthStencilState;
+use crate::lps::rasterize::pixel_output::PixelOutput;
+use crate::lps::rasterize::pixel_quad::PixelQuad;
+use crate::lps::rasterize::primitive_topology::PrimitiveTopology;
+use crate::lps::rasterize::rasterizer_state::RasterizerState;
+use crate::lps::rasterize::render_target::MAX_COLOR_ATTACHMENTS;
+use crate::lps::rasterize::viewport::Viewport;
 
 pub trait VertexShader<Input, Output> {
     fn handle(&self, vertex: &Input) -> Output;
 
-    fn init_constant_buffer(&mut self, buffer: &Vec<Option<Arc<dyn Any + Send>>>);
+    /// The constant buffer slots `init_constant_buffer` reads.
+    fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding>;
+
+    /// Takes the values of the declared slots, called before a draw when they changed.
+    fn init_constant_buffer(&mut self, buffer: &ConstantBuffers);
 }
 
 pub trait PixelShader<Input> {
-    fn handle(&self, pixel_fragment: &Input) -> Vec4;
+    /// Shades `pixel_fragment`, `pixel_quad` gives access to the screen space derivatives
+    /// of its attributes. `None` discards the fragment, it writes neither color nor depth.
+    fn handle(&self, pixel_fragment: &Input, pixel_quad: &PixelQuad<Input>) -> Option<PixelOutput>;
+
+    /// Tells whether `handle` can discard fragments. The depth and stencil tests of such a
+    /// shader run after shading, so discarded fragments leave the stencil buffer alone.
+    fn discards(&self) -> bool {
+        false
+    }
+
+    /// The constant buffer slots `init_constant_buffer` reads.
+    fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding>;
 
-    fn init_constant_buffer(&mut self, buffer: &Vec<Option<Arc<dyn Any + Send>>>);
+    /// Takes the values of the declared slots, called before a draw when they changed.
+    fn init_constant_buffer(&mut self, buffer: &ConstantBuffers);
 }
 
-pub struct PipeLine<VSInput, VSOutput> {
-    vertex_shader: Option<Box<dyn VertexShader<VSInput, VSOutput> + Send + Sync>>,
-    pixel_shader: Option<Box<dyn PixelShader<VSOutput> + Send + Sync>>,
+/// The fixed-function state of the Gpu. Binding a pipeline state overwrites everything but
+/// the viewport and the scissor rectangle.
+pub struct PipeLine {
+    rasterizer_state: RasterizerState,
+    depth_stencil_state: DepthStencilState,
+    // one per color attachment
+    blend_states: [BlendState; MAX_COLOR_ATTACHMENTS],
+    primitive_topology: PrimitiveTopology,
+    viewport: Option<Viewport>,
+    scissor: Option<Rect>,
 }
 
-impl<VSInput, VSOutput> PipeLine<VSInput, VSOutput> {
-    pub fn new(
-        vertex_shader: Option<Box<dyn VertexShader<VSInput, VSOutput> + Send + Sync>>,
-        pixel_shader: Option<Box<dyn PixelShader<VSOutput> + Send + Sync>>,
-    ) -> Self {
+impl PipeLine {
+    pub fn new() -> Self {
         PipeLine {
-            vertex_shader,
-            pixel_shader,
+            rasterizer_state: RasterizerState::default(),
+            depth_stencil_state: DepthStencilState::default(),
+            blend_states: [BlendState::default(); MAX_COLOR_ATTACHMENTS],
+            primitive_topology: PrimitiveTopology::default(),
+            viewport: None,
+            scissor: None,
         }
     }
 
-    pub fn bind_vertex_shader(
-        &mut self,
-        shader: Option<Box<dyn VertexShader<VSInput, VSOutput> + Send + Sync>>,
-    ) {
-        self.vertex_shader = shader;
+    pub fn set_rasterizer_state(&mut self, rasterizer_state: RasterizerState) {
+        self.rasterizer_state = rasterizer_state;
     }
 
-    pub fn bind_pixel_shader(
-        &mut self,
-        shader: Option<Box<dyn PixelShader<VSOutput> + Send + Sync>>,
-    ) {
-        self.pixel_shader = shader;
+    pub fn rasterizer_state(&self) -> &RasterizerState {
+        &self.rasterizer_state
     }
 
-    pub fn handle_vertex_shader(
-        &mut self,
-        vertex: &VSInput,
-        constant_buffer: &Vec<Option<Arc<dyn Any + Send>>>,
-    ) -> VSOutput {
-        if let None = self.vertex_shader {
-            panic!("vertex shader is not bound");
-        }
+    pub fn set_depth_stencil_state(&mut self, depth_stencil_state: DepthStencilState) {
+        self.depth_stencil_state = depth_stencil_state;
+    }
 
-        let vertex_shader = self.vertex_shader.as_mut().unwrap();
-        vertex_shader.init_constant_buffer(&constant_buffer);
-        vertex_shader.handle(vertex)
+    pub fn depth_stencil_state(&self) -> &DepthStencilState {
+        &self.depth_stencil_state
     }
 
-    pub fn handle_pixel_shader(
-        &mut self,
-        pixel_fragment: &VSOutput,
-        constant_buffer: &Vec<Option<Arc<dyn Any + Send>>>,
-    ) -> Vec4 {
-        if let None = self.pixel_shader {
-            panic!("pixel shader is not bound");
-        }
+    /// Sets the blend state of every color attachment.
+    pub fn set_blend_state(&mut self, blend_state: BlendState) {
+        self.blend_states = [blend_state; MAX_COLOR_ATTACHMENTS];
+    }
+
+    /// Sets the blend state of one color attachment, `attachment` has to be below
+    /// `MAX_COLOR_ATTACHMENTS`, the Gpu checks it before.
+    pub fn set_attachment_blend_state(&mut self, attachment: usize, blend_state: BlendState) {
+        self.blend_states[attachment] = blend_state;
+    }
+
+    pub fn blend_states(&self) -> &[BlendState; MAX_COLOR_ATTACHMENTS] {
+        &self.blend_states
+    }
+
+    pub fn set_primitive_topology(&mut self, primitive_topology: PrimitiveTopology) {
+        self.primitive_topology = primitive_topology;
+    }
+
+    pub fn primitive_topology(&self) -> PrimitiveTopology {
+        self.primitive_topology
+    }
+
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = Some(viewport);
+    }
+
+    /// The viewport set by `set_viewport`, `None` until then, which covers the whole
+    /// render target.
+    pub fn viewport(&self) -> Option<Viewport> {
+        self.viewport
+    }
+
+    pub fn set_scissor(&mut self, scissor: Option<Rect>) {
+        self.scissor = scissor;
+    }
+
+    pub fn scissor(&self) -> Option<Rect> {
+        self.scissor
+    }
+}
 
-        let pixel_shader = self.pixel_shader.as_mut().unwrap();
-        pixel_shader.init_constant_buffer(&constant_buffer);
-        return pixel_shader.handle(pixel_fragment);
+impl Default for PipeLine {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,147 @@
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::blend_state::BlendState;
+use crate::lps::rasterize::constant_buffer::{ConstantBufferBinding, ConstantBuffers};
+use crate::lps::rasterize::depth_stencil_state::DepthStencilState;
+use crate::lps::rasterize::draw_call::DrawCall;
+use crate::lps::rasterize::pipeline::{PipeLine, PixelShader, VertexShader};
+use crate::lps::rasterize::primitive_topology::PrimitiveTopology;
+use crate::lps::rasterize::rasterizer_state::RasterizerState;
+use crate::lps::rasterize::render_target::MAX_COLOR_ATTACHMENTS;
+use crate::lps::rasterize::vt_output::VertexShaderOutputPositionAndLerp;
+
+/// The shaders and fixed-function state of a material, created once and bound as a whole
+/// with `SetPipelineCmd`. Binding it overwrites the topology, rasterizer, depth stencil and
+/// blend state, the `set_*` commands can still change them afterwards.
+pub struct PipelineState<VSInput, VSOutput> {
+    vertex_shader: Box<dyn VertexShader<VSInput, VSOutput> + Send + Sync>,
+    pixel_shader: Box<dyn PixelShader<VSOutput> + Send + Sync>,
+    primitive_topology: PrimitiveTopology,
+    rasterizer_state: RasterizerState,
+    depth_stencil_state: DepthStencilState,
+    blend_states: [BlendState; MAX_COLOR_ATTACHMENTS],
+}
+
+impl<VSInput, VSOutput> PipelineState<VSInput, VSOutput> {
+    pub fn new(
+        vertex_shader: Box<dyn VertexShader<VSInput, VSOutput> + Send + Sync>,
+        pixel_shader: Box<dyn PixelShader<VSOutput> + Send + Sync>,
+    ) -> Self {
+        PipelineState {
+            vertex_shader,
+            pixel_shader,
+            primitive_topology: PrimitiveTopology::default(),
+            rasterizer_state: RasterizerState::default(),
+            depth_stencil_state: DepthStencilState::default(),
+            blend_states: [BlendState::default(); MAX_COLOR_ATTACHMENTS],
+        }
+    }
+
+    pub fn with_primitive_topology(mut self, primitive_topology: PrimitiveTopology) -> Self {
+        self.primitive_topology = primitive_topology;
+        self
+    }
+
+    pub fn with_rasterizer_state(mut self, rasterizer_state: RasterizerState) -> Self {
+        self.rasterizer_state = rasterizer_state;
+        self
+    }
+
+    pub fn with_depth_stencil_state(mut self, depth_stencil_state: DepthStencilState) -> Self {
+        self.depth_stencil_state = depth_stencil_state;
+        self
+    }
+
+    /// Blends every color attachment with `blend_state`.
+    pub fn with_blend_state(mut self, blend_state: BlendState) -> Self {
+        self.blend_states = [blend_state; MAX_COLOR_ATTACHMENTS];
+        self
+    }
+
+    /// Blends one color attachment with `blend_state`, fails like
+    /// `GpuApi::set_attachment_blend_state` for an attachment out of range.
+    pub fn with_attachment_blend_state(
+        mut self,
+        attachment: usize,
+        blend_state: BlendState,
+    ) -> Result<Self, GpuError> {
+        if attachment >= MAX_COLOR_ATTACHMENTS {
+            return Err(GpuError::InvalidResource(format!(
+                "color attachment {} is out of range",
+                attachment
+            )));
+        }
+        self.blend_states[attachment] = blend_state;
+        Ok(self)
+    }
+}
+
+/// A pipeline state with its vertex types erased, so that pipeline states of different
+/// vertex formats can be created on one Gpu and picked per draw.
+pub trait AnyPipelineState: Send + Sync {
+    /// The constant buffer slots the shaders declare.
+    fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding>;
+
+    /// Hands the constant buffers to the shaders, so that vertices and pixels can be shaded
+    /// through a shared reference from several threads.
+    fn init_constant_buffers(&mut self, buffers: &ConstantBuffers);
+
+    /// Overwrites the fixed-function state of `pipe_line` with the one of the state.
+    fn apply(&self, pipe_line: &mut PipeLine);
+
+    /// Runs the draw through the shaders, fails if the vertex buffer does not hold the
+    /// vertex input type of the state.
+    fn draw(&self, draw_call: DrawCall) -> Result<(), GpuError>;
+
+    /// Checks every slot the shaders declare before a draw.
+    fn validate_constant_buffers(&self, buffers: &ConstantBuffers) -> Result<(), GpuError> {
+        self.constant_buffer_layout()
+            .iter()
+            .try_for_each(|binding| binding.validate(buffers.get_any(binding.slot)))
+    }
+}
+
+impl<VSInput, VSOutput> AnyPipelineState for PipelineState<VSInput, VSOutput>
+where
+    VSInput: 'static + Sync + Send + Copy,
+    VSOutput: 'static + VertexShaderOutputPositionAndLerp + Sync + Send + Copy,
+{
+    fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+        let mut layout = self.vertex_shader.constant_buffer_layout();
+        layout.extend(self.pixel_shader.constant_buffer_layout());
+        layout
+    }
+
+    fn init_constant_buffers(&mut self, buffers: &ConstantBuffers) {
+        self.vertex_shader.init_constant_buffer(buffers);
+        self.pixel_shader.init_constant_buffer(buffers);
+    }
+
+    fn apply(&self, pipe_line: &mut PipeLine) {
+        pipe_line.set_primitive_topology(self.primitive_topology);
+        pipe_line.set_rasterizer_state(self.rasterizer_state);
+        pipe_line.set_depth_stencil_state(self.depth_stencil_state);
+        for (attachment, blend_state) in self.blend_states.iter().enumerate() {
+            pipe_line.set_attachment_blend_state(attachment, *blend_state);
+        }
+    }
+
+    fn draw(&self, draw_call: DrawCall) -> Result<(), GpuError> {
+        draw_call.execute(self.vertex_shader.as_ref(), self.pixel_shader.as_ref())
+    }
+}
+
+/// A pipeline state on its way to the Gpu, any vertex types will do.
+pub struct PipelineStateData(Box<dyn AnyPipelineState>);
+
+impl PipelineStateData {
+    pub fn new<VSInput, VSOutput>(pipeline_state: PipelineState<VSInput, VSOutput>) -> Self
+    where
+        PipelineState<VSInput, VSOutput>: AnyPipelineState + 'static,
+    {
+        PipelineStateData(Box::new(pipeline_state))
+    }
+
+    pub fn into_inner(self) -> Box<dyn AnyPipelineState> {
+        self.0
+    }
+}
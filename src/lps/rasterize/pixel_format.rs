@@ -0,0 +1,77 @@
+use crate::lps::common::color::Color;
+use crate::lps::common::math::vec2::Vec2;
+use crate::lps::common::math::vec4::Vec4;
+
+/// How the color plane of a render target stores a sample. Colors are given in 0..255 like
+/// the pixel shader output, `Rgba8` saturates them to bytes while the float formats keep
+/// them as they are, so light can add up above 255 for a later tonemapping pass.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PixelFormat {
+    #[default]
+    Rgba8,
+    Rgba32F,
+    R32F,
+    Rg32F,
+}
+
+impl PixelFormat {
+    /// Tells whether the format stores values clamped to 0..255, blending on it is clamped
+    /// as well.
+    pub fn is_normalized(&self) -> bool {
+        *self == PixelFormat::Rgba8
+    }
+}
+
+/// A sample of a color plane. Channels a format lacks read as 0, alpha as 255.
+pub trait Texel: Copy {
+    fn to_vec4(&self) -> Vec4;
+    fn from_vec4(color: &Vec4) -> Self;
+}
+
+impl Texel for Color {
+    fn to_vec4(&self) -> Vec4 {
+        Vec4::new(self.r as f32, self.g as f32, self.b as f32, self.a as f32)
+    }
+
+    /// Clamps every channel to 0..255 and drops the fraction, NaN turns into 0.
+    fn from_vec4(color: &Vec4) -> Self {
+        let [r, g, b, a] = [color.x, color.y, color.z, color.w].map(|v| {
+            if v.is_nan() {
+                0
+            } else {
+                v.clamp(0.0, 255.0) as u8
+            }
+        });
+        Color::new_rgba(r, g, b, a)
+    }
+}
+
+impl Texel for Vec4 {
+    fn to_vec4(&self) -> Vec4 {
+        *self
+    }
+
+    fn from_vec4(color: &Vec4) -> Self {
+        *color
+    }
+}
+
+impl Texel for f32 {
+    fn to_vec4(&self) -> Vec4 {
+        Vec4::new(*self, 0.0, 0.0, 255.0)
+    }
+
+    fn from_vec4(color: &Vec4) -> Self {
+        color.x
+    }
+}
+
+impl Texel for Vec2 {
+    fn to_vec4(&self) -> Vec4 {
+        Vec4::new(self.x, self.y, 0.0, 255.0)
+    }
+
+    fn from_vec4(color: &Vec4) -> Self {
+        Vec2::new(color.x, color.y)
+    }
+}
@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::color::Color;
+    use crate::lps::common::math::vec2::Vec2;
+    use crate::lps::common::math::vec4::Vec4;
+    use crate::lps::rasterize::pixel_format::Texel;
+
+    #[test]
+    fn test_rgba8_saturates() {
+        let color = Color::from_vec4(&Vec4::new(300.0, -20.0, 127.9, f32::NAN));
+        assert_eq!(color, Color::new_rgba(255, 0, 127, 0));
+
+        let color = Color::from_vec4(&Vec4::new(255.0, 0.0, 1e9, f32::INFINITY));
+        assert_eq!(color, Color::new_rgba(255, 0, 255, 255));
+    }
+
+    #[test]
+    fn test_missing_channels() {
+        let hdr = Vec4::new(1000.0, 2000.0, 3000.0, 4000.0);
+        assert_eq!(f32::from_vec4(&hdr), 1000.0);
+        assert_eq!(
+            f32::from_vec4(&hdr).to_vec4(),
+            Vec4::new(1000.0, 0.0, 0.0, 255.0)
+        );
+
+        let rg = Vec2::from_vec4(&hdr).to_vec4();
+        assert_eq!(rg, Vec4::new(1000.0, 2000.0, 0.0, 255.0));
+        assert_eq!(Vec4::from_vec4(&hdr), hdr);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::lps::common::math::vec4::Vec4;
+use crate::lps::rasterize::render_target::MAX_COLOR_ATTACHMENTS;
+
+/// The colors a pixel shader writes, one per color attachment. Attachments the shader has
+/// no color for keep their value, a single color converts into one for the first attachment.
+#[derive(Clone, Copy, Debug)]
+pub struct PixelOutput {
+    colors: [Option<Vec4>; MAX_COLOR_ATTACHMENTS],
+}
+
+impl PixelOutput {
+    pub fn new() -> Self {
+        PixelOutput {
+            colors: [None; MAX_COLOR_ATTACHMENTS],
+        }
+    }
+
+    pub fn with_color(mut self, attachment: usize, color: Vec4) -> Self {
+        self.colors[attachment] = Some(color);
+        self
+    }
+
+    pub fn color(&self, attachment: usize) -> Option<&Vec4> {
+        self.colors.get(attachment)?.as_ref()
+    }
+}
+
+impl Default for PixelOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Vec4> for PixelOutput {
+    fn from(color: Vec4) -> Self {
+        PixelOutput::new().with_color(0, color)
+    }
+}
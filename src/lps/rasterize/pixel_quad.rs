@@ -0,0 +1,39 @@
+use std::ops::Sub;
+
+/// The 2x2 block of fragments a pixel is shaded in. Lanes are ordered (0, 0), (1, 0),
+/// (0, 1), (1, 1); lanes outside the primitive still hold extrapolated fragments, so
+/// screen space derivatives are available everywhere.
+pub struct PixelQuad<'a, Input> {
+    fragments: &'a [Input; 4],
+    lane: usize,
+}
+
+impl<'a, Input> PixelQuad<'a, Input> {
+    pub fn new(fragments: &'a [Input; 4], lane: usize) -> Self {
+        Self { fragments, lane }
+    }
+
+    pub fn fragment(&self) -> &Input {
+        &self.fragments[self.lane]
+    }
+
+    /// Difference of an attribute between the right and the left pixel of this row.
+    pub fn ddx<T, F>(&self, attribute: F) -> T
+    where
+        T: Sub<Output = T>,
+        F: Fn(&Input) -> T,
+    {
+        let row = self.lane & 2;
+        attribute(&self.fragments[row + 1]) - attribute(&self.fragments[row])
+    }
+
+    /// Difference of an attribute between the lower and the upper pixel of this column.
+    pub fn ddy<T, F>(&self, attribute: F) -> T
+    where
+        T: Sub<Output = T>,
+        F: Fn(&Input) -> T,
+    {
+        let column = self.lane & 1;
+        attribute(&self.fragments[column + 2]) - attribute(&self.fragments[column])
+    }
+}
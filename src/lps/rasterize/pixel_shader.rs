@@ -1,26 +1,43 @@
 use super::pipeline::PixelShader;
+use super::pixel_output::PixelOutput;
+use super::pixel_quad::PixelQuad;
 use super::vt_output::VertexShaderOutput;
 use crate::lps::common::math::vec4::Vec4;
+use crate::lps::common::sampler::Sampler;
 use crate::lps::common::texture::Texture;
-use std::sync::Mutex;
-use std::{any::Any, sync::Arc};
+use crate::lps::rasterize::constant_buffer::{ConstantBufferBinding, ConstantBuffers};
+use std::sync::Arc;
 
 pub struct CustomPixelShader {
-    texture: Option<Arc<Mutex<Texture>>>,
+    texture: Option<Arc<Texture>>,
+    sampler: Sampler,
 }
 
 impl CustomPixelShader {
     pub fn new() -> CustomPixelShader {
-        CustomPixelShader { texture: None }
+        CustomPixelShader {
+            texture: None,
+            sampler: Sampler::default(),
+        }
     }
 }
 
 impl PixelShader<VertexShaderOutput> for CustomPixelShader {
-    fn handle(&self, pixel_fragment: &VertexShaderOutput) -> Vec4 {
-        if let Some(texture) = self.texture.as_ref() {
-            let texture = texture.lock().unwrap();
-            let color = texture.sample2d(pixel_fragment.texcoord.clone());
-            Vec4::new(color.r as f32, color.g as f32, color.b as f32, 255.0)
+    fn handle(
+        &self,
+        pixel_fragment: &VertexShaderOutput,
+        pixel_quad: &PixelQuad<VertexShaderOutput>,
+    ) -> Option<PixelOutput> {
+        let color = if let Some(texture) = self.texture.as_ref() {
+            let ddx = pixel_quad.ddx(|fragment| fragment.texcoord);
+            let ddy = pixel_quad.ddy(|fragment| fragment.texcoord);
+            let color = texture.sample_grad(&self.sampler, pixel_fragment.texcoord, ddx, ddy);
+            Vec4::new(
+                color.r as f32,
+                color.g as f32,
+                color.b as f32,
+                color.a as f32,
+            )
         } else {
             Vec4::new(
                 pixel_fragment.color.x,
@@ -28,17 +45,20 @@ impl PixelShader<VertexShaderOutput> for CustomPixelShader {
                 pixel_fragment.color.z,
                 255.0,
             )
-        }
+        };
+        Some(color.into())
+    }
+
+    fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+        vec![
+            ConstantBufferBinding::required::<Arc<Texture>>(3),
+            ConstantBufferBinding::optional::<Sampler>(4),
+        ]
     }
 
-    fn init_constant_buffer(&mut self, buffer: &Vec<Option<Arc<dyn Any + Send>>>) {
-        self.texture = Some(
-            buffer[3]
-                .as_ref()
-                .unwrap()
-                .downcast_ref::<Arc<Mutex<Texture>>>()
-                .unwrap()
-                .clone(),
-        );
+    fn init_constant_buffer(&mut self, buffer: &ConstantBuffers) {
+        self.texture = buffer.get::<Arc<Texture>>(3).cloned();
+        // the sampler slot is optional, an unbound slot samples with the default sampler
+        self.sampler = buffer.get::<Sampler>(4).copied().unwrap_or_default();
     }
 }
@@ -0,0 +1,80 @@
+use crate::lps::rasterize::rasterizer_state::ProvokingVertex;
+
+/// How the vertex stream of a draw is assembled into primitives.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PrimitiveTopology {
+    PointList,
+    LineList,
+    LineStrip,
+    #[default]
+    TriangleList,
+    TriangleStrip,
+    TriangleFan,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Primitive<T> {
+    Point(T),
+    Line([T; 2]),
+    Triangle([T; 3]),
+}
+
+impl<T> Primitive<T> {
+    pub fn vertices(&self) -> &[T] {
+        match self {
+            Primitive::Point(v) => std::slice::from_ref(v),
+            Primitive::Line(vertices) => vertices,
+            Primitive::Triangle(vertices) => vertices,
+        }
+    }
+}
+
+impl PrimitiveTopology {
+    /// Splits a stream of `vertex_cnt` vertices into primitives of positions in the stream.
+    /// Incomplete primitives at the end are dropped. Every other triangle of a strip has
+    /// its first two vertices swapped, so all of them keep the winding of the first one.
+    pub fn primitives(&self, vertex_cnt: usize) -> Vec<Primitive<usize>> {
+        match self {
+            PrimitiveTopology::PointList => (0..vertex_cnt).map(Primitive::Point).collect(),
+            PrimitiveTopology::LineList => (0..vertex_cnt / 2)
+                .map(|i| Primitive::Line([i * 2, i * 2 + 1]))
+                .collect(),
+            PrimitiveTopology::LineStrip => (1..vertex_cnt)
+                .map(|i| Primitive::Line([i - 1, i]))
+                .collect(),
+            PrimitiveTopology::TriangleList => (0..vertex_cnt / 3)
+                .map(|i| Primitive::Triangle([i * 3, i * 3 + 1, i * 3 + 2]))
+                .collect(),
+            PrimitiveTopology::TriangleStrip => (2..vertex_cnt)
+                .map(|i| {
+                    if i % 2 == 0 {
+                        Primitive::Triangle([i - 2, i - 1, i])
+                    } else {
+                        Primitive::Triangle([i - 1, i - 2, i])
+                    }
+                })
+                .collect(),
+            PrimitiveTopology::TriangleFan => (2..vertex_cnt)
+                .map(|i| Primitive::Triangle([0, i - 1, i]))
+                .collect(),
+        }
+    }
+
+    /// The position in the stream of the vertex whose flat varyings `primitive` takes.
+    pub fn provoking_vertex(
+        &self,
+        primitive: &Primitive<usize>,
+        provoking_vertex: ProvokingVertex,
+    ) -> usize {
+        let vertices = primitive.vertices().iter().copied();
+        match (provoking_vertex, self, primitive) {
+            // every triangle of a fan starts at the shared center, the first of its own vertices
+            // provokes it
+            (ProvokingVertex::First, PrimitiveTopology::TriangleFan, Primitive::Triangle(v)) => {
+                v[1]
+            }
+            (ProvokingVertex::First, _, _) => vertices.min().unwrap(),
+            (ProvokingVertex::Last, _, _) => vertices.max().unwrap(),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::rasterize::primitive_topology::{Primitive, PrimitiveTopology};
+    use crate::lps::rasterize::rasterizer_state::ProvokingVertex;
+
+    #[test]
+    fn test_list_topologies() {
+        assert_eq!(
+            PrimitiveTopology::PointList.primitives(2),
+            vec![Primitive::Point(0), Primitive::Point(1)]
+        );
+        // the incomplete primitives at the end are dropped
+        assert_eq!(
+            PrimitiveTopology::LineList.primitives(5),
+            vec![Primitive::Line([0, 1]), Primitive::Line([2, 3])]
+        );
+        assert_eq!(
+            PrimitiveTopology::TriangleList.primitives(7),
+            vec![
+                Primitive::Triangle([0, 1, 2]),
+                Primitive::Triangle([3, 4, 5])
+            ]
+        );
+        assert!(PrimitiveTopology::TriangleList.primitives(2).is_empty());
+    }
+
+    #[test]
+    fn test_strip_and_fan_topologies() {
+        assert_eq!(
+            PrimitiveTopology::LineStrip.primitives(3),
+            vec![Primitive::Line([0, 1]), Primitive::Line([1, 2])]
+        );
+        assert!(PrimitiveTopology::LineStrip.primitives(1).is_empty());
+
+        // odd triangles of a strip swap their first two vertices to keep the winding
+        assert_eq!(
+            PrimitiveTopology::TriangleStrip.primitives(5),
+            vec![
+                Primitive::Triangle([0, 1, 2]),
+                Primitive::Triangle([2, 1, 3]),
+                Primitive::Triangle([2, 3, 4]),
+            ]
+        );
+        assert_eq!(
+            PrimitiveTopology::TriangleFan.primitives(5),
+            vec![
+                Primitive::Triangle([0, 1, 2]),
+                Primitive::Triangle([0, 2, 3]),
+                Primitive::Triangle([0, 3, 4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_provoking_vertex() {
+        let provoking = |topology: PrimitiveTopology, provoking_vertex: ProvokingVertex| {
+            topology
+                .primitives(5)
+                .iter()
+                .map(|primitive| topology.provoking_vertex(primitive, provoking_vertex))
+                .collect::<Vec<usize>>()
+        };
+
+        assert_eq!(
+            provoking(PrimitiveTopology::LineStrip, ProvokingVertex::First),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            provoking(PrimitiveTopology::LineStrip, ProvokingVertex::Last),
+            vec![1, 2, 3, 4]
+        );
+        // the swapped vertices of odd strip triangles do not change the provoking vertex
+        assert_eq!(
+            provoking(PrimitiveTopology::TriangleStrip, ProvokingVertex::First),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            provoking(PrimitiveTopology::TriangleStrip, ProvokingVertex::Last),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            provoking(PrimitiveTopology::TriangleFan, ProvokingVertex::First),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            provoking(PrimitiveTopology::TriangleFan, ProvokingVertex::Last),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            provoking(PrimitiveTopology::PointList, ProvokingVertex::Last),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::lps::common::math::vec4::Vec4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CullMode {
+    None,
+    Front,
+    Back,
+}
+
+/// Winding of a front facing triangle, as seen on screen with y pointing up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrontFace {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// How the triangles that survive culling are drawn, `Wireframe` draws their edges as
+/// lines and `Point` their vertices as points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FillMode {
+    Solid,
+    Wireframe,
+    Point,
+}
+
+/// The vertex of a primitive whose flat varyings the whole primitive takes. `First` is the
+/// oldest vertex of the stream, the second one for the triangles of a fan, `Last` the newest.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ProvokingVertex {
+    #[default]
+    First,
+    Last,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RasterizerState {
+    pub cull_mode: CullMode,
+    pub front_face: FrontFace,
+    pub fill_mode: FillMode,
+    /// Added to the screen space depth of every vertex, a small negative bias lets a
+    /// wireframe pass the depth test against the solid pass of the same mesh.
+    pub depth_bias: f32,
+    pub provoking_vertex: ProvokingVertex,
+}
+
+impl RasterizerState {
+    pub fn new(cull_mode: CullMode, front_face: FrontFace) -> Self {
+        Self {
+            cull_mode,
+            front_face,
+            fill_mode: FillMode::Solid,
+            depth_bias: 0.0,
+            provoking_vertex: ProvokingVertex::First,
+        }
+    }
+
+    pub fn with_fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.fill_mode = fill_mode;
+        self
+    }
+
+    pub fn with_depth_bias(mut self, depth_bias: f32) -> Self {
+        self.depth_bias = depth_bias;
+        self
+    }
+
+    pub fn with_provoking_vertex(mut self, provoking_vertex: ProvokingVertex) -> Self {
+        self.provoking_vertex = provoking_vertex;
+        self
+    }
+
+    /// Tells whether a triangle given in clip space is culled. The orientation comes from
+    /// the homogeneous determinant, which stays valid for vertices behind the camera, so
+    /// the test can run before clipping.
+    pub fn is_culled(&self, p0: &Vec4, p1: &Vec4, p2: &Vec4) -> bool {
+        if self.cull_mode == CullMode::None {
+            return false;
+        }
+
+        if RasterizerState::determinant(p0, p1, p2) == 0.0 {
+            return true;
+        }
+
+        let front = self.is_front_facing(p0, p1, p2);
+        match self.cull_mode {
+            CullMode::None => false,
+            CullMode::Front => front,
+            CullMode::Back => !front,
+        }
+    }
+
+    /// Tells whether a triangle given in clip space faces the viewer.
+    pub fn is_front_facing(&self, p0: &Vec4, p1: &Vec4, p2: &Vec4) -> bool {
+        let counter_clockwise = RasterizerState::determinant(p0, p1, p2) > 0.0;
+        counter_clockwise == (self.front_face == FrontFace::CounterClockwise)
+    }
+
+    fn determinant(p0: &Vec4, p1: &Vec4, p2: &Vec4) -> f32 {
+        p0.x * (p1.y * p2.w - p2.y * p1.w) - p1.x * (p0.y * p2.w - p2.y * p0.w)
+            + p2.x * (p0.y * p1.w - p1.y * p0.w)
+    }
+}
+
+impl Default for RasterizerState {
+    fn default() -> Self {
+        Self::new(CullMode::None, FrontFace::CounterClockwise)
+    }
+}
@@ -1,14 +1,46 @@
 use crate::lps::common::math::vec4::Vec4;
 use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
 use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use std::ops::BitOr;
+
+/// Selects the buffers of the render target a clear resets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClearFlags(u8);
+
+impl ClearFlags {
+    pub const COLOR: ClearFlags = ClearFlags(1);
+    pub const DEPTH: ClearFlags = ClearFlags(2);
+    pub const STENCIL: ClearFlags = ClearFlags(4);
+
+    pub fn contains(&self, other: ClearFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ClearFlags {
+    type Output = ClearFlags;
+
+    fn bitor(self, rhs: ClearFlags) -> ClearFlags {
+        ClearFlags(self.0 | rhs.0)
+    }
+}
 
 pub struct ClearCmd {
+    pub flags: ClearFlags,
     pub color: Vec4,
+    pub depth: f32,
+    pub stencil: u8,
 }
 
 impl ClearCmd {
-    pub fn new(color: Vec4) -> ClearCmd {
-        ClearCmd { color }
+    pub fn new(flags: ClearFlags, color: Vec4, depth: f32, stencil: u8) -> ClearCmd {
+        ClearCmd {
+            flags,
+            color,
+            depth,
+            stencil,
+        }
     }
 }
 
@@ -17,7 +49,7 @@ impl RenderCmd for ClearCmd {
         RenderCommandType::Clear
     }
 
-    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) {
-        gpu_api.clear(&self.color);
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.clear(self.flags, &self.color, self.depth, self.stencil)
     }
 }
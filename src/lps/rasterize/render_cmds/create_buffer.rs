@@ -0,0 +1,28 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::buffer::BufferData;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::BufferId;
+
+/// Stores `data` on the Gpu under `id`. Executing it again only clones the `Arc` of the
+/// data.
+pub struct CreateBufferCmd {
+    pub id: BufferId,
+    pub data: BufferData,
+}
+
+impl CreateBufferCmd {
+    pub fn new(id: BufferId, data: BufferData) -> CreateBufferCmd {
+        CreateBufferCmd { id, data }
+    }
+}
+
+impl RenderCmd for CreateBufferCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::CreateBuffer
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.create_buffer(self.id, self.data.clone())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::pipeline_state::PipelineStateData;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::PipelineStateId;
+use std::cell::Cell;
+
+/// Stores a pipeline state on the Gpu under `id`. The shaders are moved to the Gpu, so the
+/// command can only be executed once.
+pub struct CreatePipelineStateCmd {
+    pub id: PipelineStateId,
+    pub pipeline_state: Cell<Option<PipelineStateData>>,
+}
+
+impl CreatePipelineStateCmd {
+    pub fn new(id: PipelineStateId, pipeline_state: PipelineStateData) -> CreatePipelineStateCmd {
+        CreatePipelineStateCmd {
+            id,
+            pipeline_state: Cell::new(Some(pipeline_state)),
+        }
+    }
+}
+
+impl RenderCmd for CreatePipelineStateCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::CreatePipelineState
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        match self.pipeline_state.take() {
+            Some(pipeline_state) => gpu_api.create_pipeline_state(self.id, pipeline_state),
+            None => Err(GpuError::InvalidResource(format!(
+                "{:?} was already created by this command",
+                self.id
+            ))),
+        }
+    }
+}
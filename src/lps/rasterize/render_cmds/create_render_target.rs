@@ -0,0 +1,49 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::pixel_format::PixelFormat;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::RenderTargetId;
+
+/// Allocates a render target on the Gpu under `id`, with `sample_cnt` samples per pixel
+/// stored in `format`.
+pub struct CreateRenderTargetCmd {
+    pub id: RenderTargetId,
+    pub width: u32,
+    pub height: u32,
+    pub sample_cnt: u32,
+    pub format: PixelFormat,
+}
+
+impl CreateRenderTargetCmd {
+    pub fn new(
+        id: RenderTargetId,
+        width: u32,
+        height: u32,
+        sample_cnt: u32,
+        format: PixelFormat,
+    ) -> Self {
+        CreateRenderTargetCmd {
+            id,
+            width,
+            height,
+            sample_cnt,
+            format,
+        }
+    }
+}
+
+impl RenderCmd for CreateRenderTargetCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::CreateRenderTarget
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.create_render_target(
+            self.id,
+            self.width,
+            self.height,
+            self.sample_cnt,
+            self.format,
+        )
+    }
+}
@@ -0,0 +1,28 @@
+use crate::lps::common::texture::Texture;
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::TextureId;
+use std::sync::Arc;
+
+/// Stores `texture` on the Gpu under `id`.
+pub struct CreateTextureCmd {
+    pub id: TextureId,
+    pub texture: Arc<Texture>,
+}
+
+impl CreateTextureCmd {
+    pub fn new(id: TextureId, texture: Arc<Texture>) -> CreateTextureCmd {
+        CreateTextureCmd { id, texture }
+    }
+}
+
+impl RenderCmd for CreateTextureCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::CreateTexture
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.create_texture(self.id, Arc::clone(&self.texture))
+    }
+}
@@ -0,0 +1,24 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::BufferId;
+
+pub struct DestroyBufferCmd {
+    pub id: BufferId,
+}
+
+impl DestroyBufferCmd {
+    pub fn new(id: BufferId) -> DestroyBufferCmd {
+        DestroyBufferCmd { id }
+    }
+}
+
+impl RenderCmd for DestroyBufferCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::DestroyBuffer
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.destroy_buffer(self.id)
+    }
+}
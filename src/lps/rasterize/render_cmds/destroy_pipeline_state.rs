@@ -0,0 +1,24 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::PipelineStateId;
+
+pub struct DestroyPipelineStateCmd {
+    pub id: PipelineStateId,
+}
+
+impl DestroyPipelineStateCmd {
+    pub fn new(id: PipelineStateId) -> DestroyPipelineStateCmd {
+        DestroyPipelineStateCmd { id }
+    }
+}
+
+impl RenderCmd for DestroyPipelineStateCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::DestroyPipelineState
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.destroy_pipeline_state(self.id)
+    }
+}
@@ -0,0 +1,24 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::RenderTargetId;
+
+pub struct DestroyRenderTargetCmd {
+    pub id: RenderTargetId,
+}
+
+impl DestroyRenderTargetCmd {
+    pub fn new(id: RenderTargetId) -> DestroyRenderTargetCmd {
+        DestroyRenderTargetCmd { id }
+    }
+}
+
+impl RenderCmd for DestroyRenderTargetCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::DestroyRenderTarget
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.destroy_render_target(self.id)
+    }
+}
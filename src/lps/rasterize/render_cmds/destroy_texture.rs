@@ -0,0 +1,24 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::TextureId;
+
+pub struct DestroyTextureCmd {
+    pub id: TextureId,
+}
+
+impl DestroyTextureCmd {
+    pub fn new(id: TextureId) -> DestroyTextureCmd {
+        DestroyTextureCmd { id }
+    }
+}
+
+impl RenderCmd for DestroyTextureCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::DestroyTexture
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.destroy_texture(self.id)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
 use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
 
 pub struct DrawCmd {
@@ -22,7 +23,7 @@ impl RenderCmd for DrawCmd {
         RenderCommandType::Draw
     }
 
-    fn execute(&self, gpu_buffer: &mut (dyn GpuApi + Sync + Send)) {
-        gpu_buffer.draw(self.draw_with_index);
+    fn execute(&self, gpu_buffer: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_buffer.draw(self.draw_with_index)
     }
 }
@@ -1,8 +1,30 @@
 pub mod clear;
+pub mod create_buffer;
+pub mod create_pipeline_state;
+pub mod create_render_target;
+pub mod create_texture;
+pub mod destroy_buffer;
+pub mod destroy_pipeline_state;
+pub mod destroy_render_target;
+pub mod destroy_texture;
 pub mod draw;
+pub mod read_render_target;
 pub mod render_cmd;
+pub mod resolve;
+pub mod set_attachment_blend_state;
+pub mod set_blend_state;
 pub mod set_constant_buffer;
+pub mod set_depth_stencil_state;
 pub mod set_index_buffer;
+pub mod set_pipeline;
+pub mod set_primitive_topology;
+pub mod set_rasterizer_state;
 pub mod set_render_target;
+pub mod set_render_targets;
+pub mod set_scissor;
+pub mod set_texture;
 pub mod set_vertex_buffer;
+pub mod set_viewport;
 pub mod swap;
+pub mod update_buffer;
+pub mod update_texture;
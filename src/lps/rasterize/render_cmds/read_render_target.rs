@@ -0,0 +1,31 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::render_target::RenderTarget;
+use crate::lps::rasterize::resource::RenderTargetId;
+use std::sync::mpsc::Sender;
+
+/// Sends a copy of the render target `id` back to the Cpu. The result of the read goes
+/// through `sender`, so a failed read is reported to the caller instead of the Gpu.
+pub struct ReadRenderTargetCmd {
+    pub id: RenderTargetId,
+    pub sender: Sender<Result<RenderTarget, GpuError>>,
+}
+
+impl ReadRenderTargetCmd {
+    pub fn new(id: RenderTargetId, sender: Sender<Result<RenderTarget, GpuError>>) -> Self {
+        ReadRenderTargetCmd { id, sender }
+    }
+}
+
+impl RenderCmd for ReadRenderTargetCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::ReadRenderTarget
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        // the reader may have given up waiting, there is nobody left to tell then
+        let _ = self.sender.send(gpu_api.read_render_target(self.id));
+        Ok(())
+    }
+}
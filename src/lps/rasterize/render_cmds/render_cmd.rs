@@ -1,4 +1,5 @@
 use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
 
 #[derive(Debug)]
 pub enum RenderCommandType {
@@ -9,9 +10,31 @@ pub enum RenderCommandType {
     Clear = 4,
     Swap = 5,
     SetIndexBuffer = 6,
+    SetRasterizerState = 7,
+    SetBlendState = 8,
+    SetDepthStencilState = 9,
+    Resolve = 10,
+    SetPrimitiveTopology = 11,
+    SetViewport = 12,
+    SetScissor = 13,
+    CreateBuffer = 14,
+    UpdateBuffer = 15,
+    DestroyBuffer = 16,
+    CreateTexture = 17,
+    UpdateTexture = 18,
+    DestroyTexture = 19,
+    SetTexture = 20,
+    CreateRenderTarget = 21,
+    DestroyRenderTarget = 22,
+    ReadRenderTarget = 23,
+    CreatePipelineState = 24,
+    DestroyPipelineState = 25,
+    SetPipeline = 26,
+    SetRenderTargets = 27,
+    SetAttachmentBlendState = 28,
 }
 
 pub trait RenderCmd: Send {
     fn cmd_type(&self) -> RenderCommandType;
-    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send));
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError>;
 }
@@ -0,0 +1,25 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::RenderTargetId;
+
+/// Resolves the bound multisampled render target into `dst`.
+pub struct ResolveCmd {
+    pub dst: RenderTargetId,
+}
+
+impl ResolveCmd {
+    pub fn new(dst: RenderTargetId) -> ResolveCmd {
+        ResolveCmd { dst }
+    }
+}
+
+impl RenderCmd for ResolveCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::Resolve
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.resolve(self.dst)
+    }
+}
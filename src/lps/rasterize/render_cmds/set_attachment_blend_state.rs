@@ -0,0 +1,29 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::blend_state::BlendState;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+
+/// Sets the blend state of a single color attachment, `SetBlendStateCmd` sets all of them.
+pub struct SetAttachmentBlendStateCmd {
+    pub attachment: usize,
+    pub blend_state: BlendState,
+}
+
+impl SetAttachmentBlendStateCmd {
+    pub fn new(attachment: usize, blend_state: BlendState) -> SetAttachmentBlendStateCmd {
+        SetAttachmentBlendStateCmd {
+            attachment,
+            blend_state,
+        }
+    }
+}
+
+impl RenderCmd for SetAttachmentBlendStateCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetAttachmentBlendState
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_attachment_blend_state(self.attachment, self.blend_state)
+    }
+}
@@ -0,0 +1,25 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::blend_state::BlendState;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+
+pub struct SetBlendStateCmd {
+    pub blend_state: BlendState,
+}
+
+impl SetBlendStateCmd {
+    pub fn new(blend_state: BlendState) -> SetBlendStateCmd {
+        SetBlendStateCmd { blend_state }
+    }
+}
+
+impl RenderCmd for SetBlendStateCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetBlendState
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_blend_state(self.blend_state);
+        Ok(())
+    }
+}
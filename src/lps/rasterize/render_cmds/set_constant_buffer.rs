@@ -1,10 +1,9 @@
-use crate::lps::common::math::mat4x4::Mat4x4;
-use crate::lps::common::math::vec4::Vec4;
-use crate::lps::common::texture::Texture;
 use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::constant_buffer::ConstantBuffer;
 use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
 use std::any::Any;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 pub struct SetConstantBufferCmd {
     pub layout_index: usize,
@@ -12,24 +11,7 @@ pub struct SetConstantBufferCmd {
 }
 
 impl SetConstantBufferCmd {
-    pub fn new_with_mat4x4(layout_index: usize, buffer: Mat4x4) -> SetConstantBufferCmd {
-        SetConstantBufferCmd {
-            layout_index,
-            buffer: Arc::new(buffer),
-        }
-    }
-
-    pub fn new_with_vec4(layout_index: usize, buffer: Vec4) -> SetConstantBufferCmd {
-        SetConstantBufferCmd {
-            layout_index,
-            buffer: Arc::new(buffer),
-        }
-    }
-
-    pub fn new_with_texture(
-        layout_index: usize,
-        buffer: Arc<Mutex<Texture>>,
-    ) -> SetConstantBufferCmd {
+    pub fn new<T: ConstantBuffer>(layout_index: usize, buffer: T) -> SetConstantBufferCmd {
         SetConstantBufferCmd {
             layout_index,
             buffer: Arc::new(buffer),
@@ -42,7 +24,7 @@ impl RenderCmd for SetConstantBufferCmd {
         RenderCommandType::SetConstantBuffer
     }
 
-    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) {
-        gpu_api.set_constant_buffer(self.layout_index, Arc::clone(&self.buffer));
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_constant_buffer(self.layout_index, Arc::clone(&self.buffer))
     }
 }
@@ -0,0 +1,27 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::depth_stencil_state::DepthStencilState;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+
+pub struct SetDepthStencilStateCmd {
+    pub depth_stencil_state: DepthStencilState,
+}
+
+impl SetDepthStencilStateCmd {
+    pub fn new(depth_stencil_state: DepthStencilState) -> SetDepthStencilStateCmd {
+        SetDepthStencilStateCmd {
+            depth_stencil_state,
+        }
+    }
+}
+
+impl RenderCmd for SetDepthStencilStateCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetDepthStencilState
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_depth_stencil_state(self.depth_stencil_state);
+        Ok(())
+    }
+}
@@ -1,12 +1,14 @@
+use crate::lps::core::gpu_error::GpuError;
 use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::BufferId;
 
 pub struct SetIndexBufferCmd {
-    pub index_list: Vec<usize>,
+    pub buffer: BufferId,
 }
 
 impl SetIndexBufferCmd {
-    pub fn new(index_list: Vec<usize>) -> Self {
-        Self { index_list }
+    pub fn new(buffer: BufferId) -> Self {
+        Self { buffer }
     }
 }
 
@@ -15,7 +17,10 @@ impl RenderCmd for SetIndexBufferCmd {
         RenderCommandType::SetIndexBuffer
     }
 
-    fn execute(&self, gpu_api: &mut (dyn crate::lps::core::gpu::GpuApi + Sync + Send)) {
-        gpu_api.set_index_buffer(self.index_list.clone());
+    fn execute(
+        &self,
+        gpu_api: &mut (dyn crate::lps::core::gpu::GpuApi + Sync + Send),
+    ) -> Result<(), GpuError> {
+        gpu_api.set_index_buffer(self.buffer)
     }
 }
@@ -0,0 +1,25 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::PipelineStateId;
+
+/// Binds the shaders and fixed-function state of the pipeline state `pipeline_state`.
+pub struct SetPipelineCmd {
+    pub pipeline_state: PipelineStateId,
+}
+
+impl SetPipelineCmd {
+    pub fn new(pipeline_state: PipelineStateId) -> SetPipelineCmd {
+        SetPipelineCmd { pipeline_state }
+    }
+}
+
+impl RenderCmd for SetPipelineCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetPipeline
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_pipeline(self.pipeline_state)
+    }
+}
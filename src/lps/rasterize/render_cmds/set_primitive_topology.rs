@@ -0,0 +1,25 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::primitive_topology::PrimitiveTopology;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+
+pub struct SetPrimitiveTopologyCmd {
+    pub primitive_topology: PrimitiveTopology,
+}
+
+impl SetPrimitiveTopologyCmd {
+    pub fn new(primitive_topology: PrimitiveTopology) -> SetPrimitiveTopologyCmd {
+        SetPrimitiveTopologyCmd { primitive_topology }
+    }
+}
+
+impl RenderCmd for SetPrimitiveTopologyCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetPrimitiveTopology
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_primitive_topology(self.primitive_topology);
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::rasterizer_state::RasterizerState;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+
+pub struct SetRasterizerStateCmd {
+    pub rasterizer_state: RasterizerState,
+}
+
+impl SetRasterizerStateCmd {
+    pub fn new(rasterizer_state: RasterizerState) -> SetRasterizerStateCmd {
+        SetRasterizerStateCmd { rasterizer_state }
+    }
+}
+
+impl RenderCmd for SetRasterizerStateCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetRasterizerState
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_rasterizer_state(self.rasterizer_state);
+        Ok(())
+    }
+}
@@ -1,15 +1,14 @@
-use std::sync::{Arc, Mutex};
-
 use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
 use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
-use crate::lps::rasterize::render_target::RenderTarget;
+use crate::lps::rasterize::resource::RenderTargetId;
 
 pub struct SetRenderTargetCmd {
-    pub render_target: Arc<Mutex<RenderTarget>>,
+    pub render_target: RenderTargetId,
 }
 
 impl SetRenderTargetCmd {
-    pub fn new(render_target: Arc<Mutex<RenderTarget>>) -> SetRenderTargetCmd {
+    pub fn new(render_target: RenderTargetId) -> SetRenderTargetCmd {
         SetRenderTargetCmd { render_target }
     }
 }
@@ -19,7 +18,7 @@ impl RenderCmd for SetRenderTargetCmd {
         RenderCommandType::SetRenderTarget
     }
 
-    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) {
-        gpu_api.set_render_target(Arc::clone(&self.render_target));
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_render_target(self.render_target)
     }
 }
@@ -0,0 +1,33 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::RenderTargetId;
+
+/// Binds the color planes of `color_attachments` and the depth and stencil planes of
+/// `depth_attachment`, a pixel shader writes its n-th color to the n-th color attachment.
+pub struct SetRenderTargetsCmd {
+    pub color_attachments: Vec<RenderTargetId>,
+    pub depth_attachment: Option<RenderTargetId>,
+}
+
+impl SetRenderTargetsCmd {
+    pub fn new(
+        color_attachments: Vec<RenderTargetId>,
+        depth_attachment: Option<RenderTargetId>,
+    ) -> SetRenderTargetsCmd {
+        SetRenderTargetsCmd {
+            color_attachments,
+            depth_attachment,
+        }
+    }
+}
+
+impl RenderCmd for SetRenderTargetsCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetRenderTargets
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_render_targets(&self.color_attachments, self.depth_attachment)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::lps::common::rect::Rect;
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+
+/// Restricts drawing to a rectangle of the render target, `None` disables the scissor test.
+pub struct SetScissorCmd {
+    pub scissor: Option<Rect>,
+}
+
+impl SetScissorCmd {
+    pub fn new(scissor: Option<Rect>) -> SetScissorCmd {
+        SetScissorCmd { scissor }
+    }
+}
+
+impl RenderCmd for SetScissorCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetScissor
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_scissor(self.scissor);
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::TextureId;
+
+/// Binds the texture `texture` to the constant buffer slot `layout_index`, the shaders
+/// get it as `Arc<Texture>`.
+pub struct SetTextureCmd {
+    pub layout_index: usize,
+    pub texture: TextureId,
+}
+
+impl SetTextureCmd {
+    pub fn new(layout_index: usize, texture: TextureId) -> SetTextureCmd {
+        SetTextureCmd {
+            layout_index,
+            texture,
+        }
+    }
+}
+
+impl RenderCmd for SetTextureCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetTexture
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_texture(self.layout_index, self.texture)
+    }
+}
@@ -1,15 +1,15 @@
 use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
 use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
-use std::any::Any;
-use std::sync::Arc;
+use crate::lps::rasterize::resource::BufferId;
 
 pub struct SetVertexBufferCmd {
-    pub vertex_list: Vec<Arc<dyn Any + Send + Sync>>,
+    pub buffer: BufferId,
 }
 
 impl SetVertexBufferCmd {
-    pub fn new(vertex_list: Vec<Arc<dyn Any + Send + Sync>>) -> SetVertexBufferCmd {
-        SetVertexBufferCmd { vertex_list }
+    pub fn new(buffer: BufferId) -> SetVertexBufferCmd {
+        SetVertexBufferCmd { buffer }
     }
 }
 
@@ -18,8 +18,7 @@ impl RenderCmd for SetVertexBufferCmd {
         RenderCommandType::SetVertexBuffer
     }
 
-    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) {
-        let copy = self.vertex_list.clone();
-        gpu_api.set_vertex_buffer(copy);
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_vertex_buffer(self.buffer)
     }
 }
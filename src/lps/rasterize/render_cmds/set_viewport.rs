@@ -0,0 +1,25 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::viewport::Viewport;
+
+pub struct SetViewportCmd {
+    pub viewport: Viewport,
+}
+
+impl SetViewportCmd {
+    pub fn new(viewport: Viewport) -> SetViewportCmd {
+        SetViewportCmd { viewport }
+    }
+}
+
+impl RenderCmd for SetViewportCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::SetViewport
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.set_viewport(self.viewport);
+        Ok(())
+    }
+}
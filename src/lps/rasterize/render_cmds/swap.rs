@@ -1,4 +1,5 @@
 use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
 use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
 
 pub struct Swap {}
@@ -14,7 +15,8 @@ impl RenderCmd for Swap {
         RenderCommandType::Swap
     }
 
-    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) {
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
         gpu_api.swap();
+        Ok(())
     }
 }
@@ -0,0 +1,27 @@
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::buffer::BufferData;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::BufferId;
+
+/// Replaces the contents of the buffer `id`, which has to keep its kind.
+pub struct UpdateBufferCmd {
+    pub id: BufferId,
+    pub data: BufferData,
+}
+
+impl UpdateBufferCmd {
+    pub fn new(id: BufferId, data: BufferData) -> UpdateBufferCmd {
+        UpdateBufferCmd { id, data }
+    }
+}
+
+impl RenderCmd for UpdateBufferCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::UpdateBuffer
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.update_buffer(self.id, self.data.clone())
+    }
+}
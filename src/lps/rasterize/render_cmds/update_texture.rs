@@ -0,0 +1,28 @@
+use crate::lps::common::texture::Texture;
+use crate::lps::core::gpu::GpuApi;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::render_cmds::render_cmd::{RenderCmd, RenderCommandType};
+use crate::lps::rasterize::resource::TextureId;
+use std::sync::Arc;
+
+/// Replaces the texture `id`, draws queued before keep sampling the old one.
+pub struct UpdateTextureCmd {
+    pub id: TextureId,
+    pub texture: Arc<Texture>,
+}
+
+impl UpdateTextureCmd {
+    pub fn new(id: TextureId, texture: Arc<Texture>) -> UpdateTextureCmd {
+        UpdateTextureCmd { id, texture }
+    }
+}
+
+impl RenderCmd for UpdateTextureCmd {
+    fn cmd_type(&self) -> RenderCommandType {
+        RenderCommandType::UpdateTexture
+    }
+
+    fn execute(&self, gpu_api: &mut (dyn GpuApi + Sync + Send)) -> Result<(), GpuError> {
+        gpu_api.update_texture(self.id, Arc::clone(&self.texture))
+    }
+}
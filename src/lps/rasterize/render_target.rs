@@ -1,85 +1,220 @@
 use crate::lps::common::color::Color;
+use crate::lps::common::math::vec2::Vec2;
+use crate::lps::common::math::vec4::Vec4;
+use crate::lps::common::rect::Rect;
+use crate::lps::rasterize::pixel_format::{PixelFormat, Texel};
 use bmp::{Image, Pixel};
 
+// depth values are in 0..1 after the viewport transform, 1 is the far plane
+const DEFAULT_DEPTH: f32 = 1.0;
+
+/// Sample positions are given in 1/16 pixel.
+pub const SAMPLE_POSITION_BITS: u32 = 4;
+
+/// The number of color attachments a draw can write at once.
+pub const MAX_COLOR_ATTACHMENTS: usize = 8;
+
+/// The most samples a render target holds, 16384x16384 at 4x. The planes of a larger one
+/// take tens of gigabytes.
+pub const MAX_RENDER_TARGET_SAMPLES: usize = 1 << 30;
+
+// standard sample positions as offsets from the pixel center
+const SAMPLE_PATTERN_1X: [(i32, i32); 1] = [(0, 0)];
+const SAMPLE_PATTERN_2X: [(i32, i32); 2] = [(4, 4), (-4, -4)];
+const SAMPLE_PATTERN_4X: [(i32, i32); 4] = [(-2, -6), (6, -2), (-6, 2), (2, 6)];
+const SAMPLE_PATTERN_8X: [(i32, i32); 8] = [
+    (1, -3),
+    (-1, 3),
+    (5, 1),
+    (-3, -5),
+    (-5, 5),
+    (-7, -1),
+    (3, 7),
+    (7, -7),
+];
+
+/// The color plane of a render target, stored in its pixel format.
+#[derive(Clone)]
+enum ColorPlane {
+    Rgba8(Vec<Color>),
+    Rgba32F(Vec<Vec4>),
+    R32F(Vec<f32>),
+    Rg32F(Vec<Vec2>),
+}
+
+impl ColorPlane {
+    fn new(format: PixelFormat, size: usize) -> ColorPlane {
+        match format {
+            PixelFormat::Rgba8 => ColorPlane::Rgba8(vec![Color::BLUE; size]),
+            PixelFormat::Rgba32F => ColorPlane::Rgba32F(vec![Vec4::ZERO; size]),
+            PixelFormat::R32F => ColorPlane::R32F(vec![0.0; size]),
+            PixelFormat::Rg32F => ColorPlane::Rg32F(vec![Vec2::ZERO; size]),
+        }
+    }
+
+    fn format(&self) -> PixelFormat {
+        match self {
+            ColorPlane::Rgba8(_) => PixelFormat::Rgba8,
+            ColorPlane::Rgba32F(_) => PixelFormat::Rgba32F,
+            ColorPlane::R32F(_) => PixelFormat::R32F,
+            ColorPlane::Rg32F(_) => PixelFormat::Rg32F,
+        }
+    }
+
+    fn get(&self, idx: usize) -> Vec4 {
+        match self {
+            ColorPlane::Rgba8(plane) => plane[idx].to_vec4(),
+            ColorPlane::Rgba32F(plane) => plane[idx].to_vec4(),
+            ColorPlane::R32F(plane) => plane[idx].to_vec4(),
+            ColorPlane::Rg32F(plane) => plane[idx].to_vec4(),
+        }
+    }
+
+    fn fill(&mut self, color: &Vec4) {
+        match self {
+            ColorPlane::Rgba8(plane) => plane.fill(Texel::from_vec4(color)),
+            ColorPlane::Rgba32F(plane) => plane.fill(Texel::from_vec4(color)),
+            ColorPlane::R32F(plane) => plane.fill(Texel::from_vec4(color)),
+            ColorPlane::Rg32F(plane) => plane.fill(Texel::from_vec4(color)),
+        }
+    }
+}
+
+/// Color, depth and stencil planes. A multisampled target keeps `sample_cnt` values of
+/// every plane per pixel, stored next to each other.
+#[derive(Clone)]
 pub struct RenderTarget {
     width: u32,
     height: u32,
-    buffer: Vec<Color>,
+    sample_cnt: u32,
+    buffer: ColorPlane,
     depth_buffer: Vec<f32>,
+    stencil_buffer: Vec<u8>,
 }
 
 impl RenderTarget {
     pub fn new(w: u32, h: u32) -> RenderTarget {
+        RenderTarget::new_multisampled(w, h, 1)
+    }
+
+    /// Creates an RGBA8 target with 1, 2, 4 or 8 samples per pixel.
+    pub fn new_multisampled(w: u32, h: u32, sample_cnt: u32) -> RenderTarget {
+        RenderTarget::new_with_format(w, h, sample_cnt, PixelFormat::Rgba8)
+    }
+
+    pub fn new_with_format(w: u32, h: u32, sample_cnt: u32, format: PixelFormat) -> RenderTarget {
+        if ![1, 2, 4, 8].contains(&sample_cnt) {
+            panic!("unsupported sample count: {}", sample_cnt);
+        }
+
+        let size = RenderTarget::sample_len(w, h, sample_cnt).expect("render target too large");
         RenderTarget {
             width: w,
             height: h,
-            buffer: vec![Color::BLUE; usize::try_from(w * h).unwrap()],
-            depth_buffer: vec![10000.0; usize::try_from(w * h).unwrap()],
+            sample_cnt,
+            buffer: ColorPlane::new(format, size),
+            depth_buffer: vec![DEFAULT_DEPTH; size],
+            stencil_buffer: vec![0; size],
         }
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.width = width;
-        self.height = height;
-        self.buffer = vec![Color::BLUE; usize::try_from(width * height).unwrap()];
-        self.depth_buffer = vec![10000.0; usize::try_from(width * height).unwrap()];
+    /// The number of samples in every plane of a target, `None` if it overflows `usize`.
+    pub fn sample_len(w: u32, h: u32, sample_cnt: u32) -> Option<usize> {
+        let [w, h, sample_cnt] = [w, h, sample_cnt].map(|v| usize::try_from(v).ok());
+        w?.checked_mul(h?)?.checked_mul(sample_cnt?)
     }
 
-    pub fn clear(&mut self, color: Color) {
-        for i in 0..self.width() {
-            for j in 0..self.height() {
-                *self.get_mut_pixel(i, j) = color;
-                *self.get_depth(i, j) = 10000.0;
-            }
-        }
+    pub fn resize(&mut self, width: u32, height: u32) {
+        *self = RenderTarget::new_with_format(width, height, self.sample_cnt, self.format());
     }
 
-    pub fn get_screen_depth(&mut self, x: i32, y: i32) -> f32 {
-        let screen_x = (x + self.width() as i32 / 2) as u32;
-        let screen_y = (self.height() as i32 / 2 - y) as u32;
+    /// Stores `color`, given in 0..255, in every sample in the format of the target.
+    pub fn clear_color(&mut self, color: &Vec4) {
+        self.buffer.fill(color);
+    }
 
-        *self.get_depth(screen_x, screen_y)
+    pub fn clear_depth(&mut self, depth: f32) {
+        self.depth_buffer.fill(depth);
     }
 
-    fn get_depth(&mut self, x: u32, y: u32) -> &mut f32 {
-        let idx = usize::try_from(y * self.width() + x).unwrap();
-        &mut self.depth_buffer[idx]
+    pub fn clear_stencil(&mut self, stencil: u8) {
+        self.stencil_buffer.fill(stencil);
     }
 
-    pub fn get_pixel(&self, x: u32, y: u32) -> &Color {
-        let idx = usize::try_from(y * self.width() + x).unwrap();
-        &self.buffer[idx]
+    /// Depth of the first sample of a pixel.
+    pub fn depth(&self, x: u32, y: u32) -> f32 {
+        self.depth_buffer[self.sample_index(x, y, 0)]
     }
 
-    fn get_mut_pixel(&mut self, x: u32, y: u32) -> &mut Color {
-        let idx = usize::try_from(y * self.width() + x).unwrap();
-        &mut self.buffer[idx]
+    /// Stencil value of the first sample of a pixel.
+    pub fn stencil(&self, x: u32, y: u32) -> u8 {
+        self.stencil_buffer[self.sample_index(x, y, 0)]
     }
 
-    pub fn draw_point(&mut self, x: i32, y: i32, color: &Color) {
-        // logic screen (0, 0) -> render target (width / 2, height / 2)
-        // logic screen (x, y) -> render target (x + width / 2, height / 2 - y)
-        let screen_x = (x + self.width() as i32 / 2) as u32;
-        let screen_y = (self.height() as i32 / 2 - y) as u32;
+    /// Color of the first sample of a pixel, a multisampled target has to be resolved
+    /// for the final color. Panics unless the target is RGBA8.
+    pub fn get_pixel(&self, x: u32, y: u32) -> &Color {
+        self.get_sample(x, y, 0)
+    }
 
-        if screen_x >= self.width() || screen_y >= self.height() {
-            return;
+    pub fn get_sample(&self, x: u32, y: u32, sample: u32) -> &Color {
+        match &self.buffer {
+            ColorPlane::Rgba8(plane) => &plane[self.sample_index(x, y, sample)],
+            plane => panic!("{:?} target has no RGBA8 colors", plane.format()),
         }
+    }
 
-        *self.get_mut_pixel(screen_x, screen_y) = color.clone();
+    /// A sample in any format, see `Texel` for the channels the format lacks.
+    pub fn get_texel(&self, x: u32, y: u32, sample: u32) -> Vec4 {
+        self.buffer.get(self.sample_index(x, y, sample))
     }
 
-    pub fn draw_depth(&mut self, x: i32, y: i32, depth: f32) {
-        let screen_x = (x + self.width() as i32 / 2) as u32;
-        let screen_y = (self.height() as i32 / 2 - y) as u32;
+    fn sample_index(&self, x: u32, y: u32, sample: u32) -> usize {
+        usize::try_from((y * self.width() + x) * self.sample_cnt + sample).unwrap()
+    }
 
-        if screen_x >= self.width() || screen_y >= self.height() {
-            return;
-        }
+    /// Averages the samples of every pixel into `dst`, a single sampled target of the same
+    /// size and format. Only the color plane is resolved, the Gpu checks the targets before
+    /// resolving.
+    pub fn resolve(&self, dst: &mut RenderTarget) {
+        assert!(
+            dst.width() == self.width() && dst.height() == self.height(),
+            "resolve target size {}x{} does not match {}x{}",
+            dst.width(),
+            dst.height(),
+            self.width(),
+            self.height()
+        );
+        assert_eq!(dst.sample_cnt(), 1, "resolve target is multisampled");
 
-        *self.get_depth(screen_x, screen_y) = depth;
+        let sample_cnt = self.sample_cnt;
+        match (&self.buffer, &mut dst.buffer) {
+            (ColorPlane::Rgba8(src), ColorPlane::Rgba8(dst)) => {
+                for (color, samples) in dst.iter_mut().zip(src.chunks(sample_cnt as usize)) {
+                    let mut sum = [0u32; 4];
+                    for sample in samples {
+                        sum[0] += sample.r as u32;
+                        sum[1] += sample.g as u32;
+                        sum[2] += sample.b as u32;
+                        sum[3] += sample.a as u32;
+                    }
+                    let [r, g, b, a] = sum.map(|v| ((v + sample_cnt / 2) / sample_cnt) as u8);
+                    *color = Color::new_rgba(r, g, b, a);
+                }
+            }
+            (ColorPlane::Rgba32F(src), ColorPlane::Rgba32F(dst)) => average(src, dst, sample_cnt),
+            (ColorPlane::R32F(src), ColorPlane::R32F(dst)) => average(src, dst, sample_cnt),
+            (ColorPlane::Rg32F(src), ColorPlane::Rg32F(dst)) => average(src, dst, sample_cnt),
+            (src, dst) => panic!(
+                "resolve target format {:?} does not match {:?}",
+                dst.format(),
+                src.format()
+            ),
+        }
     }
 
+    /// Saves the first sample of every pixel as a bitmap, float formats are saturated
+    /// like RGBA8 without any tonemapping.
     pub fn save(&mut self, file_name: &str) -> bool {
         let width = u32::try_from(self.width()).unwrap();
         let height = u32::try_from(self.height()).unwrap();
@@ -88,7 +223,7 @@ impl RenderTarget {
 
         for i in 0..self.width() {
             for j in 0..self.height() {
-                let color = self.get_mut_pixel(i, j);
+                let color = Color::from_vec4(&self.get_texel(i, j, 0));
                 img.set_pixel(
                     i,
                     j,
@@ -112,4 +247,300 @@ impl RenderTarget {
     pub fn height(&self) -> u32 {
         self.height
     }
+
+    pub fn sample_cnt(&self) -> u32 {
+        self.sample_cnt
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.buffer.format()
+    }
+
+    /// Sample positions as offsets from the pixel center, see `SAMPLE_POSITION_BITS`.
+    pub fn sample_pattern(&self) -> &'static [(i32, i32)] {
+        sample_pattern(self.sample_cnt)
+    }
+}
+
+// averages the float samples of every pixel into the pixels of `dst`
+fn average<T: Texel>(src: &[T], dst: &mut [T], sample_cnt: u32) {
+    for (color, samples) in dst.iter_mut().zip(src.chunks(sample_cnt as usize)) {
+        let mut sum = Vec4::ZERO;
+        for sample in samples {
+            sum += sample.to_vec4();
+        }
+        *color = T::from_vec4(&(sum / sample_cnt as f32));
+    }
+}
+
+fn sample_pattern(sample_cnt: u32) -> &'static [(i32, i32)] {
+    match sample_cnt {
+        1 => &SAMPLE_PATTERN_1X,
+        2 => &SAMPLE_PATTERN_2X,
+        4 => &SAMPLE_PATTERN_4X,
+        _ => &SAMPLE_PATTERN_8X,
+    }
+}
+
+/// The planes a draw writes to: the color planes of the color attachments and the depth
+/// and stencil planes of the depth attachment. All attachments have the same size and
+/// sample count, one render target can be a color and the depth attachment at once.
+pub struct Attachments<'a> {
+    width: u32,
+    height: u32,
+    sample_cnt: u32,
+    colors: Vec<&'a mut ColorPlane>,
+    depth_stencil: Option<(&'a mut [f32], &'a mut [u8])>,
+}
+
+impl<'a> Attachments<'a> {
+    /// Takes the color planes of the first `color_cnt` targets and the depth and stencil
+    /// planes of the target at `depth`. Panics without any target, the Gpu checks the
+    /// sizes and sample counts when the targets are bound.
+    pub fn new(
+        targets: Vec<&'a mut RenderTarget>,
+        color_cnt: usize,
+        depth: Option<usize>,
+    ) -> Attachments<'a> {
+        let (width, height, sample_cnt) = {
+            let first = &targets[0];
+            (first.width, first.height, first.sample_cnt)
+        };
+
+        let mut colors = vec![];
+        let mut depth_stencil = None;
+        for (i, target) in targets.into_iter().enumerate() {
+            let RenderTarget {
+                buffer,
+                depth_buffer,
+                stencil_buffer,
+                ..
+            } = target;
+            if i < color_cnt {
+                colors.push(buffer);
+            }
+            if depth == Some(i) {
+                depth_stencil = Some((depth_buffer.as_mut_slice(), stencil_buffer.as_mut_slice()));
+            }
+        }
+
+        Attachments {
+            width,
+            height,
+            sample_cnt,
+            colors,
+            depth_stencil,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Splits the planes into `tile_size` x `tile_size` tiles, row by row. Every tile
+    /// exclusively borrows its part of the planes, so tiles can be drawn in parallel.
+    pub fn tiles_mut(self, tile_size: u32) -> Vec<RenderTargetTile<'a>> {
+        let width = self.width;
+        let height = self.height;
+        let tiles_x = width.div_ceil(tile_size);
+        let tiles_y = height.div_ceil(tile_size);
+        let tile_cnt = (tiles_x * tiles_y) as usize;
+        let sample_cnt = self.sample_cnt;
+        let split = PlaneSplit {
+            row_len: (width * sample_cnt) as usize,
+            part_len: (tile_size * sample_cnt) as usize,
+            tile_size,
+            tiles_x,
+            tile_cnt,
+        };
+
+        let mut color_rows = (0..tile_cnt).map(|_| vec![]).collect::<Vec<_>>();
+        for plane in self.colors {
+            let rows = match plane {
+                ColorPlane::Rgba8(plane) => split.rows(plane, ColorRows::Rgba8),
+                ColorPlane::Rgba32F(plane) => split.rows(plane, ColorRows::Rgba32F),
+                ColorPlane::R32F(plane) => split.rows(plane, ColorRows::R32F),
+                ColorPlane::Rg32F(plane) => split.rows(plane, ColorRows::Rg32F),
+            };
+            for (tile, rows) in color_rows.iter_mut().zip(rows) {
+                tile.push(rows);
+            }
+        }
+        let (depth_rows, stencil_rows) = match self.depth_stencil {
+            Some((depth, stencil)) => (
+                split.rows(depth, |rows| rows),
+                split.rows(stencil, |rows| rows),
+            ),
+            None => (
+                (0..tile_cnt).map(|_| vec![]).collect(),
+                (0..tile_cnt).map(|_| vec![]).collect(),
+            ),
+        };
+
+        let sample_pattern = sample_pattern(sample_cnt);
+        let rows = color_rows.into_iter().zip(depth_rows).zip(stencil_rows);
+        rows.enumerate()
+            .map(|(i, ((color_rows, depth_rows), stencil_rows))| {
+                let x = i as u32 % tiles_x * tile_size;
+                let y = i as u32 / tiles_x * tile_size;
+                let rect = Rect::new(x, y, tile_size.min(width - x), tile_size.min(height - y));
+                RenderTargetTile {
+                    rect,
+                    scissor: rect,
+                    sample_pattern,
+                    color_rows,
+                    depth_rows,
+                    stencil_rows,
+                }
+            })
+            .collect()
+    }
+}
+
+// how the rows of a plane are cut into the parts of the tiles
+struct PlaneSplit {
+    row_len: usize,
+    part_len: usize,
+    tile_size: u32,
+    tiles_x: u32,
+    tile_cnt: usize,
+}
+
+impl PlaneSplit {
+    // the rows of every tile, wrapped by `wrap`
+    fn rows<'a, T, R>(&self, plane: &'a mut [T], wrap: impl Fn(Vec<&'a mut [T]>) -> R) -> Vec<R> {
+        let mut tiles = (0..self.tile_cnt).map(|_| vec![]).collect::<Vec<_>>();
+        for (y, row) in plane.chunks_mut(self.row_len).enumerate() {
+            let ty = (y as u32 / self.tile_size * self.tiles_x) as usize;
+            for (tx, part) in row.chunks_mut(self.part_len).enumerate() {
+                tiles[ty + tx].push(part);
+            }
+        }
+        tiles.into_iter().map(wrap).collect()
+    }
+}
+
+/// The rows of a color plane inside a tile, in the format of the plane.
+enum ColorRows<'a> {
+    Rgba8(Vec<&'a mut [Color]>),
+    Rgba32F(Vec<&'a mut [Vec4]>),
+    R32F(Vec<&'a mut [f32]>),
+    Rg32F(Vec<&'a mut [Vec2]>),
+}
+
+impl ColorRows<'_> {
+    fn format(&self) -> PixelFormat {
+        match self {
+            ColorRows::Rgba8(_) => PixelFormat::Rgba8,
+            ColorRows::Rgba32F(_) => PixelFormat::Rgba32F,
+            ColorRows::R32F(_) => PixelFormat::R32F,
+            ColorRows::Rg32F(_) => PixelFormat::Rg32F,
+        }
+    }
+
+    fn get(&self, row: usize, idx: usize) -> Vec4 {
+        match self {
+            ColorRows::Rgba8(rows) => rows[row][idx].to_vec4(),
+            ColorRows::Rgba32F(rows) => rows[row][idx].to_vec4(),
+            ColorRows::R32F(rows) => rows[row][idx].to_vec4(),
+            ColorRows::Rg32F(rows) => rows[row][idx].to_vec4(),
+        }
+    }
+
+    fn set(&mut self, row: usize, idx: usize, color: &Vec4) {
+        match self {
+            ColorRows::Rgba8(rows) => rows[row][idx] = Texel::from_vec4(color),
+            ColorRows::Rgba32F(rows) => rows[row][idx] = Texel::from_vec4(color),
+            ColorRows::R32F(rows) => rows[row][idx] = Texel::from_vec4(color),
+            ColorRows::Rg32F(rows) => rows[row][idx] = Texel::from_vec4(color),
+        }
+    }
+}
+
+/// A rectangular part of the attachments of a draw, addressed with render target
+/// coordinates and the index of a sample in the pixel. Only the pixels inside the scissor
+/// rectangle are rasterized, it covers the whole tile unless `set_scissor` narrows it down.
+pub struct RenderTargetTile<'a> {
+    rect: Rect,
+    scissor: Rect,
+    sample_pattern: &'static [(i32, i32)],
+    // the rows of every color attachment
+    color_rows: Vec<ColorRows<'a>>,
+    depth_rows: Vec<&'a mut [f32]>,
+    stencil_rows: Vec<&'a mut [u8]>,
+}
+
+impl<'a> RenderTargetTile<'a> {
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    pub fn scissor(&self) -> &Rect {
+        &self.scissor
+    }
+
+    /// Restricts rasterization to the part of the tile inside `scissor`.
+    pub fn set_scissor(&mut self, scissor: &Rect) {
+        self.scissor = self.rect.intersection(scissor);
+    }
+
+    pub fn sample_pattern(&self) -> &'static [(i32, i32)] {
+        self.sample_pattern
+    }
+
+    pub fn depth(&self, x: u32, y: u32, sample: usize) -> f32 {
+        let (row, idx) = self.index(x, y, sample);
+        self.depth_rows[row][idx]
+    }
+
+    pub fn stencil(&self, x: u32, y: u32, sample: usize) -> u8 {
+        let (row, idx) = self.index(x, y, sample);
+        self.stencil_rows[row][idx]
+    }
+
+    pub fn color_attachment_cnt(&self) -> usize {
+        self.color_rows.len()
+    }
+
+    /// Tells whether the draw has a depth attachment, without one the depth and stencil
+    /// tests pass and nothing is written to depth or stencil.
+    pub fn has_depth_stencil(&self) -> bool {
+        !self.depth_rows.is_empty()
+    }
+
+    pub fn format(&self, attachment: usize) -> PixelFormat {
+        self.color_rows[attachment].format()
+    }
+
+    /// The color of a sample in 0..255, see `Texel` for the channels the format lacks.
+    pub fn pixel(&self, attachment: usize, x: u32, y: u32, sample: usize) -> Vec4 {
+        let (row, idx) = self.index(x, y, sample);
+        self.color_rows[attachment].get(row, idx)
+    }
+
+    /// Stores `color` in the format of the attachment, RGBA8 saturates it.
+    pub fn draw_pixel(&mut self, attachment: usize, x: u32, y: u32, sample: usize, color: &Vec4) {
+        let (row, idx) = self.index(x, y, sample);
+        self.color_rows[attachment].set(row, idx, color);
+    }
+
+    pub fn draw_depth(&mut self, x: u32, y: u32, sample: usize, depth: f32) {
+        let (row, idx) = self.index(x, y, sample);
+        self.depth_rows[row][idx] = depth;
+    }
+
+    pub fn draw_stencil(&mut self, x: u32, y: u32, sample: usize, stencil: u8) {
+        let (row, idx) = self.index(x, y, sample);
+        self.stencil_rows[row][idx] = stencil;
+    }
+
+    fn index(&self, x: u32, y: u32, sample: usize) -> (usize, usize) {
+        let row = (y - self.rect.y) as usize;
+        let idx = (x - self.rect.x) as usize * self.sample_pattern.len() + sample;
+        (row, idx)
+    }
 }
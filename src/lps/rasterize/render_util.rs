@@ -1,248 +1,533 @@
-use crate::lps::common::math::vec4::Vec4;
-use crate::lps::common::{color::Color, math::vec2::Vec2};
-use crate::lps::rasterize::vt_output::VertexShaderOutputPositionAndLerp;
+use crate::lps::common::{math::vec2::Vec2, rect::Rect};
+use crate::lps::rasterize::blend_state::BlendState;
+use crate::lps::rasterize::depth_stencil_state::DepthStencilState;
+use crate::lps::rasterize::pixel_output::PixelOutput;
+use crate::lps::rasterize::vt_output::{BarycentricWeights, VertexShaderOutputPositionAndLerp};
 
-use super::render_target::RenderTarget;
+use super::render_target::{RenderTargetTile, SAMPLE_POSITION_BITS};
+
+// vertices are snapped to 1/256 of a pixel before the edge functions are evaluated
+const SUB_PIXEL_BITS: u32 = 8;
+const SUB_PIXEL_SCALE: f32 = (1 << SUB_PIXEL_BITS) as f32;
+const SUB_PIXEL_HALF: i64 = 1 << (SUB_PIXEL_BITS - 1);
 
 pub struct RenderUtil;
 
 impl RenderUtil {
+    /// Rasterizes the part of a screen space triangle that falls into the scissor rectangle
+    /// of `tile` in 2x2 pixel quads. `get_colors` receives the three vertices in their
+    /// original order and the barycentric weights of all four pixels of a quad, and shades
+    /// the pixels set in the mask, a pixel without a color is discarded. Depth and stencil
+    /// are tested per sample, `front_facing` selects the stencil face state. Pixels without
+    /// a sample passing the tests of `depth_stencil_state` are not shaded, unless `late_z`
+    /// defers the tests until after shading, so discarded pixels leave the stencil alone.
+    /// The shaded colors are merged into the covered samples of every color attachment with
+    /// the attachment's entry of `blend_states`.
     pub fn draw_triangle<Vertex, F>(
-        render_target: &mut RenderTarget,
-        v0: &Vertex,
-        v1: &Vertex,
-        v2: &Vertex,
-        mut get_color: F,
+        tile: &mut RenderTargetTile,
+        triangle: &[Vertex; 3],
+        front_facing: bool,
+        depth_stencil_state: &DepthStencilState,
+        blend_states: &[BlendState],
+        late_z: bool,
+        get_colors: &F,
     ) where
         Vertex: VertexShaderOutputPositionAndLerp,
-        F: FnMut(&Vertex, &Vertex, f32) -> Vec4,
+        F: Fn(
+            &Vertex,
+            &Vertex,
+            &Vertex,
+            &[BarycentricWeights; 4],
+            [bool; 4],
+        ) -> [Option<PixelOutput>; 4],
     {
-        let mut arr = [
-            (v0.position(), v0),
-            (v1.position(), v1),
-            (v2.position(), v2),
-        ];
-
-        // reorder vertices: v0.y <= v1.y <= v2.y
-        if arr[0].0.y > arr[1].0.y {
-            (arr[0], arr[1]) = (arr[1], arr[0]);
-        }
-
-        if arr[1].0.y > arr[2].0.y {
-            (arr[1], arr[2]) = (arr[2], arr[1]);
-        }
-
-        if arr[0].0.y > arr[1].0.y {
-            (arr[0], arr[1]) = (arr[1], arr[0]);
-        }
+        let [v0, v1, v2] = triangle;
+        let (pos0, pos1, pos2) = (v0.position(), v1.position(), v2.position());
+        let p0 = Vec2::new(pos0.x, pos0.y);
+        let p1 = Vec2::new(pos1.x, pos1.y);
+        let p2 = Vec2::new(pos2.x, pos2.y);
+        let rect = *tile.scissor();
+        let sample_pattern = tile.sample_pattern();
+        let sample_scale = (1 << SAMPLE_POSITION_BITS) as f32;
+
+        let visit = |x: u32, y: u32, mut coverage: [u8; 4], weights: [[f32; 3]; 4]| {
+            // z is linear in screen space, the pixel centers of the quad give its gradient
+            let lerp_z = weights.map(|w| w[0] * pos0.z + w[1] * pos1.z + w[2] * pos2.z);
+            let (dzdx, dzdy) = (lerp_z[1] - lerp_z[0], lerp_z[2] - lerp_z[0]);
+            let sample_depth = |lane: usize, (ox, oy): (i32, i32)| {
+                lerp_z[lane] + (ox as f32 * dzdx + oy as f32 * dzdy) / sample_scale
+            };
+            let test_quad = |tile: &mut RenderTargetTile, coverage: &mut [u8; 4]| {
+                for (lane, lane_coverage) in coverage.iter_mut().enumerate() {
+                    let (lane_x, lane_y) = (x + (lane as u32 & 1), y + (lane as u32 >> 1));
+                    for (sample, &offset) in sample_pattern.iter().enumerate() {
+                        if *lane_coverage & (1 << sample) == 0 {
+                            continue;
+                        }
+                        let depth = sample_depth(lane, offset);
+                        let pass = RenderUtil::test_sample(
+                            tile,
+                            (lane_x, lane_y, sample),
+                            depth,
+                            front_facing,
+                            depth_stencil_state,
+                        );
+                        if !pass {
+                            *lane_coverage &= !(1 << sample);
+                        }
+                    }
+                }
+            };
+
+            if !late_z {
+                test_quad(tile, &mut coverage);
+            }
+            let mask = coverage.map(|c| c != 0);
+            if !mask.contains(&true) {
+                return;
+            }
 
-        if (arr[1].0.y - arr[2].0.y).abs() < f32::EPSILON {
-            RenderUtil::draw_down_triangle(
-                render_target,
-                arr[1].1,
-                arr[2].1,
-                arr[0].1,
-                &mut get_color,
-            );
-        } else if (arr[1].0.y - arr[0].0.y).abs() < f32::EPSILON {
-            RenderUtil::draw_up_triangle(
-                render_target,
-                arr[1].1,
-                arr[0].1,
-                arr[2].1,
-                &mut get_color,
-            );
-        } else {
-            // split into 2 triangles
-            let weight = (arr[2].0.y - arr[1].0.y) / (arr[2].0.y - arr[0].0.y);
-            let new_edge = Vertex::lerp(arr[2].1, arr[0].1, weight);
-
-            RenderUtil::draw_up_triangle(
-                render_target,
-                arr[1].1,
-                &new_edge,
-                arr[2].1,
-                &mut get_color,
-            );
-            RenderUtil::draw_down_triangle(
-                render_target,
-                arr[1].1,
-                &new_edge,
-                arr[0].1,
-                &mut get_color,
-            );
-        }
+            // position().w holds 1/w of clip space, which is linear in screen space
+            let weights = weights.map(|w| {
+                let rhw = [w[0] * pos0.w, w[1] * pos1.w, w[2] * pos2.w];
+                let rhw_sum = rhw[0] + rhw[1] + rhw[2];
+                if rhw_sum.abs() < f32::EPSILON {
+                    BarycentricWeights::new(w, w)
+                } else {
+                    let perspective = [rhw[0] / rhw_sum, rhw[1] / rhw_sum, rhw[2] / rhw_sum];
+                    BarycentricWeights::new(perspective, w)
+                }
+            });
+
+            // shaded once per pixel, the colors are stored in every covered sample
+            let colors = get_colors(v0, v1, v2, &weights, mask);
+            for lane in 0..4 {
+                if colors[lane].is_none() {
+                    coverage[lane] = 0;
+                }
+            }
+            if late_z {
+                test_quad(tile, &mut coverage);
+            }
+            for lane in 0..4 {
+                let (lane_x, lane_y) = (x + (lane as u32 & 1), y + (lane as u32 >> 1));
+                let Some(output) = &colors[lane] else {
+                    continue;
+                };
+                for (sample, &offset) in sample_pattern.iter().enumerate() {
+                    if coverage[lane] & (1 << sample) == 0 {
+                        continue;
+                    }
+                    RenderUtil::write_sample(
+                        tile,
+                        (lane_x, lane_y, sample),
+                        output,
+                        sample_depth(lane, offset),
+                        depth_stencil_state,
+                        blend_states,
+                    );
+                }
+            }
+        };
+        RenderUtil::rasterize_triangle_multisampled(&rect, &p0, &p1, &p2, sample_pattern, visit);
     }
 
-    fn vec4_to_color(color: &Vec4) -> Color {
-        Color::new_rgba(color.x as u8, color.y as u8, color.z as u8, color.w as u8)
+    /// Walks the triangle (in pixel space, y pointing down) in 2x2 pixel quads aligned to
+    /// even coordinates. For every quad with a pixel inside `rect` whose center is covered,
+    /// `visit` gets the quad's top left pixel, the coverage of its pixels (ordered (0, 0),
+    /// (1, 0), (0, 1), (1, 1)) and the screen space barycentric weights of `p0`, `p1` and
+    /// `p2` for all four pixels, covered or not. Pixels on edges shared by two triangles
+    /// are covered by exactly one of them (top-left fill rule).
+    pub fn rasterize_triangle<F>(rect: &Rect, p0: &Vec2, p1: &Vec2, p2: &Vec2, mut visit: F)
+    where
+        F: FnMut(u32, u32, [bool; 4], [[f32; 3]; 4]),
+    {
+        RenderUtil::rasterize_triangle_multisampled(
+            rect,
+            p0,
+            p1,
+            p2,
+            &[(0, 0)],
+            |x, y, coverage, weights| visit(x, y, coverage.map(|c| c != 0), weights),
+        );
     }
 
-    fn draw_scan_line<Vertex, F>(
-        render_target: &mut RenderTarget,
-        left_x: i32,
-        right_x: i32,
-        y: i32,
-        left_v: &Vertex,
-        right_v: &Vertex,
-        get_color: &mut F,
-    )  where
-        Vertex: VertexShaderOutputPositionAndLerp,
-        F: FnMut(&Vertex, &Vertex, f32) -> Vec4,
+    /// Like `rasterize_triangle`, but tests the coverage at the sample positions of
+    /// `sample_pattern` (offsets from the pixel center, see `SAMPLE_POSITION_BITS`). Bit
+    /// `i` of a pixel's coverage is set when sample `i` is covered, the weights are still
+    /// those of the pixel center.
+    pub fn rasterize_triangle_multisampled<F>(
+        rect: &Rect,
+        p0: &Vec2,
+        p1: &Vec2,
+        p2: &Vec2,
+        sample_pattern: &[(i32, i32)],
+        mut visit: F,
+    ) where
+        F: FnMut(u32, u32, [u8; 4], [[f32; 3]; 4]),
     {
-        let mut x0 = left_x;
-        let mut x1 = right_x;
-        let mut left_vertex = left_v;
-        let mut right_vertex = right_v;
-
-        if x0 > x1 {
-            std::mem::swap(&mut x0, &mut x1);
-            std::mem::swap(&mut left_vertex, &mut right_vertex);
+        let snap = |p: &Vec2| {
+            (
+                (p.x * SUB_PIXEL_SCALE).round() as i64,
+                (p.y * SUB_PIXEL_SCALE).round() as i64,
+            )
+        };
+        let mut points = [snap(p0), snap(p1), snap(p2)];
+        let mut order = [0, 1, 2];
+
+        let mut area = RenderUtil::edge_function(points[0], points[1], points[2]);
+        if area == 0 {
+            return;
         }
 
-        let mut draw_x;
-        let length = x1 - x0;
-
-        for i in 0..length {
-            draw_x = x0 + i;
-
-            let factor = i as f32 / length as f32;
-
-            let lerp_color = get_color(left_vertex, right_vertex, factor);
-            let lerp_z = (1.0 - factor) * left_vertex.position().z + factor * right_vertex.position().z;
+        // make the winding clockwise on screen, so that the inside of every edge is positive
+        if area < 0 {
+            points.swap(1, 2);
+            order.swap(1, 2);
+            area = -area;
+        }
 
-            let color = RenderUtil::vec4_to_color(&lerp_color);
+        let min_x = points.iter().map(|p| p.0).min().unwrap();
+        let max_x = points.iter().map(|p| p.0).max().unwrap();
+        let min_y = points.iter().map(|p| p.1).min().unwrap();
+        let max_y = points.iter().map(|p| p.1).max().unwrap();
+
+        let to_pixel_min = |v: i64, start: u32| ((v >> SUB_PIXEL_BITS) - 1).max(start as i64);
+        let to_pixel_max = |v: i64, end: u32| ((v >> SUB_PIXEL_BITS) + 1).min(end as i64 - 1);
+        let (start_x, end_x) = (
+            to_pixel_min(min_x, rect.x),
+            to_pixel_max(max_x, rect.right()),
+        );
+        let (start_y, end_y) = (
+            to_pixel_min(min_y, rect.y),
+            to_pixel_max(max_y, rect.bottom()),
+        );
+        if start_x > end_x || start_y > end_y {
+            return;
+        }
+        let (start_x, start_y) = (start_x & !1, start_y & !1);
 
-            let depth = render_target.get_screen_depth(draw_x, y);
+        // edge i is opposite to vertex i, so its value is the weight of that vertex
+        let edges = [
+            (points[1], points[2]),
+            (points[2], points[0]),
+            (points[0], points[1]),
+        ];
+        let bias = edges.map(|(a, b)| if RenderUtil::is_top_left(a, b) { 0 } else { -1 });
+        let step_x = edges.map(|(a, b)| -(b.1 - a.1) << SUB_PIXEL_BITS);
+        let step_y = edges.map(|(a, b)| (b.0 - a.0) << SUB_PIXEL_BITS);
+
+        let origin = (
+            (start_x << SUB_PIXEL_BITS) + SUB_PIXEL_HALF,
+            (start_y << SUB_PIXEL_BITS) + SUB_PIXEL_HALF,
+        );
+        let mut row = edges.map(|(a, b)| RenderUtil::edge_function(a, b, origin));
+
+        for y in (start_y..=end_y).step_by(2) {
+            let mut quad = row;
+            for x in (start_x..=end_x).step_by(2) {
+                let mut coverage = [0u8; 4];
+                let mut weights = [[0.0; 3]; 4];
+                for lane in 0..4 {
+                    let (dx, dy) = ((lane & 1) as i64, (lane >> 1) as i64);
+                    let e = [0, 1, 2].map(|i| quad[i] + dx * step_x[i] + dy * step_y[i]);
+
+                    let (lane_x, lane_y) = (x + dx, y + dy);
+                    let in_rect = lane_x >= rect.x as i64
+                        && lane_x < rect.right() as i64
+                        && lane_y >= rect.y as i64
+                        && lane_y < rect.bottom() as i64;
+                    if in_rect {
+                        for (sample, &(ox, oy)) in sample_pattern.iter().enumerate() {
+                            let (ox, oy) = (ox as i64, oy as i64);
+                            let inside = (0..3).all(|i| {
+                                let offset =
+                                    (ox * step_x[i] + oy * step_y[i]) >> SAMPLE_POSITION_BITS;
+                                e[i] + offset + bias[i] >= 0
+                            });
+                            if inside {
+                                coverage[lane] |= 1 << sample;
+                            }
+                        }
+                    }
+
+                    for i in 0..3 {
+                        weights[lane][order[i]] = e[i] as f32 / area as f32;
+                    }
+                }
+
+                if coverage.iter().any(|&c| c != 0) {
+                    visit(x as u32, y as u32, coverage, weights);
+                }
+
+                for i in 0..3 {
+                    quad[i] += 2 * step_x[i];
+                }
+            }
 
-            if lerp_z < depth {
-                render_target.draw_point(draw_x, y, &color);
-                render_target.draw_depth(draw_x, y, lerp_z);
+            for i in 0..3 {
+                row[i] += 2 * step_y[i];
             }
         }
     }
 
-    fn draw_up_triangle<Vertex, F>(
-        render_target: &mut RenderTarget,
-        v0: &Vertex,
-        v1: &Vertex,
-        v2: &Vertex,
-        get_color: &mut F,
+    /// Draws the part of a screen space line that falls into the scissor rectangle of `tile`,
+    /// see `rasterize_line`.
+    /// `get_colors` is the one of `draw_triangle`, it gets the second vertex twice and
+    /// only the first lane of the quad is shaded. All four lanes hold the same fragment,
+    /// so lines have no screen space derivatives. Lines cover every sample of their
+    /// pixels, `front_facing` selects the stencil face state and `late_z` works as in
+    /// `draw_triangle`.
+    pub fn draw_line<Vertex, F>(
+        tile: &mut RenderTargetTile,
+        line: &[Vertex; 2],
+        front_facing: bool,
+        depth_stencil_state: &DepthStencilState,
+        blend_states: &[BlendState],
+        late_z: bool,
+        get_colors: &F,
     ) where
         Vertex: VertexShaderOutputPositionAndLerp,
-        F: FnMut(&Vertex, &Vertex, f32) -> Vec4,
+        F: Fn(
+            &Vertex,
+            &Vertex,
+            &Vertex,
+            &[BarycentricWeights; 4],
+            [bool; 4],
+        ) -> [Option<PixelOutput>; 4],
     {
-        let p0 = Vec2::new(v0.position().x, v0.position().y);
-        let p1 = Vec2::new(v1.position().x, v1.position().y);
-        let p2 = Vec2::new(v2.position().x, v2.position().y);
-
-        let mut left = if p0.x > p1.x { p0 } else { p1 };
-        let left_v = if p0.x > p1.x { v0 } else { v1 };
-
-        let right = if p0.x > p1.x { p1 } else { p0 };
-        let right_v = if p0.x > p1.x { v1 } else { v0 };
-
-        let top = p2;
-        let top_v = v2;
+        let [v0, v1] = line;
+        let (pos0, pos1) = (v0.position(), v1.position());
+        let p0 = Vec2::new(pos0.x, pos0.y);
+        let p1 = Vec2::new(pos1.x, pos1.y);
+        let rect = *tile.scissor();
+
+        RenderUtil::rasterize_line(&rect, &p0, &p1, |x, y, t| {
+            let depth = pos0.z + (pos1.z - pos0.z) * t;
+            let states = (front_facing, depth_stencil_state, blend_states, late_z);
+            RenderUtil::draw_fragment(tile, x, y, depth, states, || {
+                // position().w holds 1/w of clip space, which is linear in screen space
+                let (rhw0, rhw1) = ((1.0 - t) * pos0.w, t * pos1.w);
+                let perspective = if (rhw0 + rhw1).abs() < f32::EPSILON {
+                    t
+                } else {
+                    rhw1 / (rhw0 + rhw1)
+                };
+                let weights = BarycentricWeights::new(
+                    [1.0 - perspective, perspective, 0.0],
+                    [1.0 - t, t, 0.0],
+                );
+                let weights = [weights; 4];
+                get_colors(v0, v1, v1, &weights, [true, false, false, false])[0]
+            });
+        });
+    }
 
-        left.x = left.x.floor();
+    /// Draws a screen space point as the single pixel it falls into, shaded like a pixel of
+    /// `draw_line`.
+    pub fn draw_point<Vertex, F>(
+        tile: &mut RenderTargetTile,
+        point: &Vertex,
+        front_facing: bool,
+        depth_stencil_state: &DepthStencilState,
+        blend_states: &[BlendState],
+        late_z: bool,
+        get_colors: &F,
+    ) where
+        Vertex: VertexShaderOutputPositionAndLerp,
+        F: Fn(
+            &Vertex,
+            &Vertex,
+            &Vertex,
+            &[BarycentricWeights; 4],
+            [bool; 4],
+        ) -> [Option<PixelOutput>; 4],
+    {
+        let pos = point.position();
+        let rect = *tile.scissor();
+        if pos.x < rect.x as f32
+            || pos.x >= rect.right() as f32
+            || pos.y < rect.y as f32
+            || pos.y >= rect.bottom() as f32
+        {
+            return;
+        }
 
-        let dy = (top.y - left.y) as i32;
-        let mut curr_y = top.y as i32;
+        let (x, y) = (pos.x as u32, pos.y as u32);
+        let states = (front_facing, depth_stencil_state, blend_states, late_z);
+        RenderUtil::draw_fragment(tile, x, y, pos.z, states, || {
+            let weights = [BarycentricWeights::new([1.0, 0.0, 0.0], [1.0, 0.0, 0.0]); 4];
+            get_colors(point, point, point, &weights, [true, false, false, false])[0]
+        });
+    }
 
-        // lerp from top to bottom
-        let mut i = dy;
-        while i >= 0 {
-            let mut weight = 0.0;
-            if dy != 0 {
-                weight = i as f32 / dy as f32;
+    /// Walks a screen space line with a DDA along its major axis. Every pixel center on
+    /// that axis between `p0` and `p1` selects the pixel whose center is closest to the
+    /// line on the other axis, `visit` gets that pixel and the line parameter t of the
+    /// center (0 at `p0`, 1 at `p1`). The pixel at `p1` is left out, so the lines of a strip
+    /// do not draw the shared vertices twice.
+    pub fn rasterize_line<F>(rect: &Rect, p0: &Vec2, p1: &Vec2, mut visit: F)
+    where
+        F: FnMut(u32, u32, f32),
+    {
+        let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+        let x_major = dx.abs() >= dy.abs();
+        let (major0, minor0, delta_major, delta_minor) = if x_major {
+            (p0.x, p0.y, dx, dy)
+        } else {
+            (p0.y, p0.x, dy, dx)
+        };
+        if delta_major == 0.0 {
+            return;
+        }
+        let (major_range, minor_range) = if x_major {
+            ((rect.x, rect.right()), (rect.y, rect.bottom()))
+        } else {
+            ((rect.y, rect.bottom()), (rect.x, rect.right()))
+        };
+
+        let major1 = major0 + delta_major;
+        let start = ((major0.min(major1) - 0.5).floor() as i64).max(major_range.0 as i64);
+        let end = ((major0.max(major1) - 0.5).ceil() as i64).min(major_range.1 as i64 - 1);
+        for major in start..=end {
+            let t = (major as f32 + 0.5 - major0) / delta_major;
+            if !(0.0..1.0).contains(&t) {
+                continue;
+            }
+            let minor = (minor0 + t * delta_minor).floor() as i64;
+            if minor < minor_range.0 as i64 || minor >= minor_range.1 as i64 {
+                continue;
+            }
+            if x_major {
+                visit(major as u32, minor as u32, t);
+            } else {
+                visit(minor as u32, major as u32, t);
             }
-
-            let mut new_left = Vec2::lerp(left, top, weight);
-            let mut new_right = Vec2::lerp(right, top, weight);
-            new_left.x = new_left.x.floor();
-            new_right.x = (new_right.x + 0.5).floor();
-
-            new_left.y = curr_y as f32;
-            new_right.y = curr_y as f32;
-
-            let new_left_v = Vertex::lerp(left_v, top_v, weight);
-            let new_right_v = Vertex::lerp(right_v, top_v, weight);
-
-            RenderUtil::draw_scan_line::<Vertex, F>(
-                render_target,
-                new_left.x as i32,
-                new_right.x as i32,
-                new_left.y as i32,
-                &new_left_v,
-                &new_right_v,
-                get_color,
-            );
-
-            i -= 1;
-            curr_y -= 1;
         }
     }
 
-    fn draw_down_triangle<Vertex, F>(
-        render_target: &mut RenderTarget,
-        v0: &Vertex,
-        v1: &Vertex,
-        v2: &Vertex,
-        get_color: &mut F,
+    // tests all samples of a pixel at the same depth, the pixel is shaded if any of them
+    // passes, with `late_z` it is shaded first and tested unless it is discarded
+    fn draw_fragment<F>(
+        tile: &mut RenderTargetTile,
+        x: u32,
+        y: u32,
+        depth: f32,
+        (front_facing, depth_stencil_state, blend_states, late_z): (
+            bool,
+            &DepthStencilState,
+            &[BlendState],
+            bool,
+        ),
+        shade: F,
     ) where
-        Vertex: VertexShaderOutputPositionAndLerp,
-        F: FnMut(&Vertex, &Vertex, f32) -> Vec4,
+        F: FnOnce() -> Option<PixelOutput>,
     {
-        let p0 = Vec2::new(v0.position().x, v0.position().y);
-        let p1 = Vec2::new(v1.position().x, v1.position().y);
-        let p2 = Vec2::new(v2.position().x, v2.position().y);
-
-        let mut left = if p0.x > p1.x { p1 } else { p0 };
-        let left_v = if p0.x > p1.x { v1 } else { v0 };
-
-        let right = if p0.x > p1.x { p0 } else { p1 };
-        let right_v = if p0.x > p1.x { v0 } else { v1 };
-
-        let bottom = p2;
-        let bottom_v = v2;
+        let sample_cnt = tile.sample_pattern().len();
+        let test = |tile: &mut RenderTargetTile| {
+            (0..sample_cnt)
+                .filter(|&sample| {
+                    RenderUtil::test_sample(
+                        tile,
+                        (x, y, sample),
+                        depth,
+                        front_facing,
+                        depth_stencil_state,
+                    )
+                })
+                .collect::<Vec<usize>>()
+        };
+
+        let (passed, output) = if late_z {
+            let Some(output) = shade() else {
+                return;
+            };
+            (test(tile), output)
+        } else {
+            let passed = test(tile);
+            if passed.is_empty() {
+                return;
+            }
+            let Some(output) = shade() else {
+                return;
+            };
+            (passed, output)
+        };
+        for sample in passed {
+            RenderUtil::write_sample(
+                tile,
+                (x, y, sample),
+                &output,
+                depth,
+                depth_stencil_state,
+                blend_states,
+            );
+        }
+    }
 
-        left.x = left.x.floor();
+    // the stencil test runs first, its value is updated even for samples that fail one of
+    // the tests
+    fn test_sample(
+        tile: &mut RenderTargetTile,
+        (x, y, sample): (u32, u32, usize),
+        depth: f32,
+        front_facing: bool,
+        depth_stencil_state: &DepthStencilState,
+    ) -> bool {
+        if !tile.has_depth_stencil() {
+            return true;
+        }
 
-        let dy = (left.y - bottom.y) as i32;
-        let mut curr_y = left.y as i32;
+        let depth_pass = depth_stencil_state.depth_test(depth, tile.depth(x, y, sample));
+        let mut pass = depth_pass;
+        if depth_stencil_state.stencil_enable {
+            let stencil = tile.stencil(x, y, sample);
+            let stencil_pass = depth_stencil_state.stencil_test(front_facing, stencil);
+            let new_stencil =
+                depth_stencil_state.stencil_update(front_facing, stencil, stencil_pass, depth_pass);
+            tile.draw_stencil(x, y, sample, new_stencil);
+            pass &= stencil_pass;
+        }
+        pass
+    }
 
-        // lerp from top to bottom
-        let mut i = 0;
-        while i < dy {
-            let mut weight = 0.0;
-            if dy != 0 {
-                weight = i as f32 / dy as f32;
-            }
+    fn write_sample(
+        tile: &mut RenderTargetTile,
+        (x, y, sample): (u32, u32, usize),
+        output: &PixelOutput,
+        depth: f32,
+        depth_stencil_state: &DepthStencilState,
+        blend_states: &[BlendState],
+    ) {
+        for (attachment, blend_state) in blend_states
+            .iter()
+            .enumerate()
+            .take(tile.color_attachment_cnt())
+        {
+            let Some(color) = output.color(attachment) else {
+                continue;
+            };
+            let dst = tile.pixel(attachment, x, y, sample);
+            let color = if tile.format(attachment).is_normalized() {
+                blend_state.blend(color, &dst)
+            } else {
+                blend_state.blend_float(color, &dst)
+            };
+            tile.draw_pixel(attachment, x, y, sample, &color);
+        }
+        if tile.has_depth_stencil() && depth_stencil_state.writes_depth() {
+            tile.draw_depth(x, y, sample, depth);
+        }
+    }
 
-            let mut new_left = Vec2::lerp(left, bottom, weight);
-            let mut new_right = Vec2::lerp(right, bottom, weight);
-            new_left.x = new_left.x.floor();
-            new_right.x = (new_right.x + 0.5).floor();
-
-            new_left.y = curr_y as f32;
-            new_right.y = curr_y as f32;
-
-            let new_left_v = Vertex::lerp(left_v, bottom_v, weight);
-            let new_right_v = Vertex::lerp(right_v, bottom_v, weight);
-
-            RenderUtil::draw_scan_line::<Vertex, F>(
-                render_target,
-                new_left.x as i32,
-                new_right.x as i32,
-                new_left.y as i32,
-                &new_left_v,
-                &new_right_v,
-                get_color
-            );
+    fn edge_function(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+        (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+    }
 
-            i += 1;
-            curr_y -= 1;
-        }
+    // with clockwise winding and y pointing down, top edges go right and left edges go up
+    fn is_top_left(a: (i64, i64), b: (i64, i64)) -> bool {
+        let dx = b.0 - a.0;
+        let dy = b.1 - a.1;
+        (dy == 0 && dx > 0) || dy < 0
     }
 }
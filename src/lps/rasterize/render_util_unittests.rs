@@ -0,0 +1,251 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::math::vec2::Vec2;
+    use crate::lps::common::rect::Rect;
+    use crate::lps::rasterize::render_target::RenderTarget;
+    use crate::lps::rasterize::render_util::RenderUtil;
+
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 48;
+
+    fn coverage_of(triangles: &[[Vec2; 3]]) -> Vec<u32> {
+        let rect = Rect::new(0, 0, WIDTH, HEIGHT);
+        let mut coverage = vec![0; (WIDTH * HEIGHT) as usize];
+        for [p0, p1, p2] in triangles {
+            RenderUtil::rasterize_triangle(&rect, p0, p1, p2, |x, y, mask, weights| {
+                assert!(
+                    x % 2 == 0 && y % 2 == 0,
+                    "quad ({}, {}) is not aligned",
+                    x,
+                    y
+                );
+                for lane in 0..4 {
+                    let sum = weights[lane][0] + weights[lane][1] + weights[lane][2];
+                    assert!(
+                        (sum - 1.0).abs() < 1e-4,
+                        "weights {:?} do not sum up to 1",
+                        weights
+                    );
+                    if mask[lane] {
+                        let (lane_x, lane_y) = (x + lane as u32 % 2, y + lane as u32 / 2);
+                        coverage[(lane_y * WIDTH + lane_x) as usize] += 1;
+                    }
+                }
+            });
+        }
+        coverage
+    }
+
+    #[test]
+    fn test_triangle_fill_rule() {
+        // a rectangle whose edges run through pixel centers, split along its diagonal:
+        // centers on its left and top edges are inside, those on its right and bottom
+        // edges are not, and those on the diagonal belong to exactly one of the triangles
+        let (left, top, right, bottom) = (2.5, 1.5, 6.5, 5.5);
+        let triangles = [
+            [
+                Vec2::new(left, top),
+                Vec2::new(right, top),
+                Vec2::new(left, bottom),
+            ],
+            [
+                Vec2::new(right, top),
+                Vec2::new(right, bottom),
+                Vec2::new(left, bottom),
+            ],
+        ];
+        let coverage = coverage_of(&triangles);
+
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let expected = if (2..6).contains(&x) && (1..5).contains(&y) {
+                    1
+                } else {
+                    0
+                };
+                assert_eq!(
+                    coverage[(y * WIDTH + x) as usize],
+                    expected,
+                    "pixel ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_edges_cover_every_pixel_once() {
+        // a jittered grid of points reaching past the render target, so that its
+        // triangulation covers the whole target
+        let step = 8.0;
+        let cols = WIDTH as usize / 8 + 3;
+        let rows = HEIGHT as usize / 8 + 3;
+
+        let mut seed = 12345u32;
+        let mut jitter = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let value = ((seed >> 16) % 13) as f32 - 6.0;
+            // half of the points land exactly on pixel centers or pixel corners
+            if seed & 0x100 == 0 {
+                value / 2.0
+            } else {
+                value / 2.0 + 0.37
+            }
+        };
+
+        let mut points = vec![];
+        for j in 0..rows {
+            for i in 0..cols {
+                let x = (i as f32 - 1.0) * step;
+                let y = (j as f32 - 1.0) * step;
+                let on_border = i == 0 || j == 0 || i == cols - 1 || j == rows - 1;
+                if on_border {
+                    points.push(Vec2::new(x, y));
+                } else {
+                    points.push(Vec2::new(x + jitter(), y + jitter()));
+                }
+            }
+        }
+
+        let mut triangles = vec![];
+        for j in 0..rows - 1 {
+            for i in 0..cols - 1 {
+                let p00 = points[j * cols + i];
+                let p10 = points[j * cols + i + 1];
+                let p01 = points[(j + 1) * cols + i];
+                let p11 = points[(j + 1) * cols + i + 1];
+
+                // alternate the diagonal and the winding of the triangles
+                if (i + j) % 2 == 0 {
+                    triangles.push([p00, p10, p11]);
+                    triangles.push([p00, p01, p11]);
+                } else {
+                    triangles.push([p10, p00, p01]);
+                    triangles.push([p10, p11, p01]);
+                }
+            }
+        }
+
+        let coverage = coverage_of(&triangles);
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                assert_eq!(
+                    coverage[(y * WIDTH + x) as usize],
+                    1,
+                    "pixel ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+    #[test]
+    fn test_multisample_coverage_on_half_covered_edge() {
+        // a rectangle whose right edge runs through the pixel centers of column 10
+        let (right, bottom) = (10.5, HEIGHT as f32);
+        let triangles = [
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(right, 0.0),
+                Vec2::new(right, bottom),
+            ],
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(right, bottom),
+                Vec2::new(0.0, bottom),
+            ],
+        ];
+        let rect = Rect::new(0, 0, WIDTH, HEIGHT);
+
+        for sample_cnt in [2, 4, 8] {
+            let sample_pattern = RenderTarget::new_multisampled(1, 1, sample_cnt).sample_pattern();
+            let all_samples = ((1u32 << sample_cnt) - 1) as u8;
+            // only the samples left of the pixel center are covered on the edge
+            let left_samples = sample_pattern
+                .iter()
+                .enumerate()
+                .filter(|(_, (x, _))| *x < 0)
+                .fold(0u8, |bits, (sample, _)| bits | 1 << sample);
+            assert_eq!(left_samples.count_ones(), sample_cnt / 2);
+
+            let mut coverage = vec![0u8; (WIDTH * HEIGHT) as usize];
+            for [p0, p1, p2] in &triangles {
+                RenderUtil::rasterize_triangle_multisampled(
+                    &rect,
+                    p0,
+                    p1,
+                    p2,
+                    sample_pattern,
+                    |x, y, quad_coverage, _| {
+                        for (lane, lane_coverage) in quad_coverage.into_iter().enumerate() {
+                            let (lane_x, lane_y) = (x + lane as u32 % 2, y + lane as u32 / 2);
+                            let pixel = &mut coverage[(lane_y * WIDTH + lane_x) as usize];
+                            assert_eq!(*pixel & lane_coverage, 0, "sample covered twice");
+                            *pixel |= lane_coverage;
+                        }
+                    },
+                );
+            }
+
+            for y in 0..HEIGHT {
+                for x in 0..WIDTH {
+                    let expected = match x {
+                        0..=9 => all_samples,
+                        10 => left_samples,
+                        _ => 0,
+                    };
+                    assert_eq!(
+                        coverage[(y * WIDTH + x) as usize],
+                        expected,
+                        "{}x pixel ({}, {})",
+                        sample_cnt,
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+    }
+
+    fn line_pixels(p0: Vec2, p1: Vec2) -> Vec<(u32, u32, f32)> {
+        let rect = Rect::new(0, 0, WIDTH, HEIGHT);
+        let mut pixels = vec![];
+        RenderUtil::rasterize_line(&rect, &p0, &p1, |x, y, t| pixels.push((x, y, t)));
+        pixels
+    }
+
+    #[test]
+    fn test_line_rasterization() {
+        // one pixel per column of a flat line, the end pixel is left out
+        let pixels = line_pixels(Vec2::new(2.5, 3.2), Vec2::new(6.5, 5.2));
+        let expected = [(2, 3), (3, 3), (4, 4), (5, 4)];
+        assert_eq!(pixels.len(), expected.len());
+        for (i, &(x, y, t)) in pixels.iter().enumerate() {
+            assert_eq!((x, y), expected[i]);
+            assert!((t - i as f32 / 4.0).abs() < 1e-5, "t = {} at {}", t, i);
+        }
+
+        // steep lines step along y, t of reversed lines still starts at p0
+        let pixels = line_pixels(Vec2::new(1.5, 9.5), Vec2::new(2.5, 1.5));
+        assert_eq!(pixels.len(), 8);
+        assert!(pixels.contains(&(1, 9, 0.0)));
+        assert!(pixels.iter().all(|&(_, y, _)| (2..=9).contains(&y)));
+
+        // the lines of a strip draw every pixel once and stay inside the target
+        let strip = [
+            Vec2::new(-4.0, 10.5),
+            Vec2::new(20.5, 10.5),
+            Vec2::new(20.5, 30.5),
+            Vec2::new(80.0, 30.5),
+        ];
+        let mut coverage = vec![0; (WIDTH * HEIGHT) as usize];
+        for i in 1..strip.len() {
+            for (x, y, _) in line_pixels(strip[i - 1], strip[i]) {
+                coverage[(y * WIDTH + x) as usize] += 1;
+            }
+        }
+        assert!(coverage.iter().all(|&c| c <= 1));
+        assert_eq!(coverage.iter().sum::<u32>(), 20 + 20 + (WIDTH - 20));
+    }
+}
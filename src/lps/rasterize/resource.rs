@@ -0,0 +1,286 @@
+use crate::lps::common::texture::Texture;
+use crate::lps::core::gpu_error::GpuError;
+use crate::lps::rasterize::buffer::BufferData;
+use crate::lps::rasterize::pipeline_state::PipelineStateData;
+use crate::lps::rasterize::render_target::RenderTarget;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// A kind of resource the Gpu owns, names the handles of it in messages.
+pub trait Resource {
+    const HANDLE_NAME: &'static str;
+}
+
+impl Resource for BufferData {
+    const HANDLE_NAME: &'static str = "BufferId";
+}
+
+impl Resource for Texture {
+    const HANDLE_NAME: &'static str = "TextureId";
+}
+
+impl Resource for RenderTarget {
+    const HANDLE_NAME: &'static str = "RenderTargetId";
+}
+
+impl Resource for PipelineStateData {
+    const HANDLE_NAME: &'static str = "PipelineStateId";
+}
+
+pub type BufferId = Handle<BufferData>;
+pub type TextureId = Handle<Texture>;
+pub type RenderTargetId = Handle<RenderTarget>;
+pub type PipelineStateId = Handle<PipelineStateData>;
+
+/// Handle of a resource owned by the Gpu. The slot of a destroyed resource is reused by
+/// the next one with a new generation, so a handle that outlived its resource is told
+/// apart from the handle of the resource that took its place.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub fn new(index: u32, generation: u32) -> Self {
+        Handle {
+            index,
+            generation,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T: Resource> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(T::HANDLE_NAME)
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// Hands out the handles of one kind of resource on the Cpu, so they can be used in
+/// commands before the Gpu created the resource.
+pub struct HandleAllocator<T> {
+    // current generation of every slot and whether a live handle holds it
+    slots: Vec<(u32, bool)>,
+    free: Vec<u32>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Resource> HandleAllocator<T> {
+    pub fn new() -> Self {
+        HandleAllocator {
+            slots: vec![],
+            free: vec![],
+            marker: PhantomData,
+        }
+    }
+
+    pub fn allocate(&mut self) -> Handle<T> {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.1 = true;
+                Handle::new(index, slot.0)
+            }
+            None => {
+                self.slots.push((0, true));
+                Handle::new(self.slots.len() as u32 - 1, 0)
+            }
+        }
+    }
+
+    pub fn is_alive(&self, handle: Handle<T>) -> bool {
+        self.slots.get(handle.index()) == Some(&(handle.generation, true))
+    }
+
+    /// Releases the slot of `handle` for reuse, the handle is stale afterwards.
+    pub fn free(&mut self, handle: Handle<T>) -> Result<(), GpuError> {
+        self.check(handle)?;
+        self.slots[handle.index()] = (handle.generation + 1, false);
+        self.free.push(handle.index);
+        Ok(())
+    }
+
+    pub fn check(&self, handle: Handle<T>) -> Result<(), GpuError> {
+        if self.is_alive(handle) {
+            return Ok(());
+        }
+
+        match self.slots.get(handle.index()) {
+            Some(&(generation, _)) if handle.generation < generation => {
+                Err(GpuError::StaleHandle(format!("{:?}", handle)))
+            }
+            _ => Err(GpuError::UnknownHandle(format!("{:?}", handle))),
+        }
+    }
+}
+
+impl<T: Resource> Default for HandleAllocator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Slot<V> {
+    generation: u32,
+    value: Option<V>,
+}
+
+/// Resources of one kind on the Gpu, stored as `V` under handles of `T`. Every lookup
+/// checks the generation of the handle.
+pub struct ResourceTable<T, V = T> {
+    slots: Vec<Slot<V>>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Resource, V> ResourceTable<T, V> {
+    pub fn new() -> Self {
+        ResourceTable {
+            slots: vec![],
+            marker: PhantomData,
+        }
+    }
+
+    pub fn insert(&mut self, handle: Handle<T>, value: V) -> Result<(), GpuError> {
+        if handle.index() >= self.slots.len() {
+            self.slots.resize_with(handle.index() + 1, || Slot {
+                generation: 0,
+                value: None,
+            });
+        }
+
+        let slot = &mut self.slots[handle.index()];
+        if handle.generation < slot.generation {
+            return Err(GpuError::StaleHandle(format!("{:?}", handle)));
+        }
+        if slot.value.is_some() {
+            return Err(GpuError::HandleInUse(format!("{:?}", handle)));
+        }
+        slot.generation = handle.generation;
+        slot.value = Some(value);
+        Ok(())
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Result<&V, GpuError> {
+        let index = self.check(handle)?;
+        Ok(self.slots[index].value.as_ref().unwrap())
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Result<&mut V, GpuError> {
+        let index = self.check(handle)?;
+        Ok(self.slots[index].value.as_mut().unwrap())
+    }
+
+    /// Gets two different resources at once, to read one while writing the other.
+    pub fn get_pair_mut(
+        &mut self,
+        handle: Handle<T>,
+        other: Handle<T>,
+    ) -> Result<(&mut V, &mut V), GpuError> {
+        let (index, other_index) = (self.check(handle)?, self.check(other)?);
+        if index == other_index {
+            return Err(GpuError::InvalidResource(format!(
+                "{:?} is used twice",
+                handle
+            )));
+        }
+
+        let (low, high) = self.slots.split_at_mut(index.max(other_index));
+        let (first, second) = (
+            low[index.min(other_index)].value.as_mut().unwrap(),
+            high[0].value.as_mut().unwrap(),
+        );
+        if index < other_index {
+            Ok((first, second))
+        } else {
+            Ok((second, first))
+        }
+    }
+
+    /// Gets several different resources at once, in the order of `handles`.
+    pub fn get_many_mut(&mut self, handles: &[Handle<T>]) -> Result<Vec<&mut V>, GpuError> {
+        let indices = handles
+            .iter()
+            .map(|&handle| self.check(handle))
+            .collect::<Result<Vec<usize>, GpuError>>()?;
+        for (i, index) in indices.iter().enumerate() {
+            if indices[..i].contains(index) {
+                return Err(GpuError::InvalidResource(format!(
+                    "{:?} is used twice",
+                    handles[i]
+                )));
+            }
+        }
+
+        let mut values = self
+            .slots
+            .iter_mut()
+            .map(|slot| slot.value.as_mut())
+            .collect::<Vec<Option<&mut V>>>();
+        Ok(indices
+            .iter()
+            .map(|&index| values[index].take().unwrap())
+            .collect())
+    }
+
+    /// Destroys the resource of `handle`, the handle is stale afterwards.
+    pub fn remove(&mut self, handle: Handle<T>) -> Result<V, GpuError> {
+        let index = self.check(handle)?;
+        let slot = &mut self.slots[index];
+        slot.generation = handle.generation + 1;
+        Ok(slot.value.take().unwrap())
+    }
+
+    fn check(&self, handle: Handle<T>) -> Result<usize, GpuError> {
+        match self.slots.get(handle.index()) {
+            Some(slot) if slot.generation == handle.generation && slot.value.is_some() => {
+                Ok(handle.index())
+            }
+            Some(slot) if handle.generation < slot.generation => {
+                Err(GpuError::StaleHandle(format!("{:?}", handle)))
+            }
+            _ => Err(GpuError::UnknownHandle(format!("{:?}", handle))),
+        }
+    }
+}
+
+impl<T: Resource, V> Default for ResourceTable<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::texture::Texture;
+    use crate::lps::core::gpu_error::GpuError;
+    use crate::lps::rasterize::resource::{HandleAllocator, ResourceTable, TextureId};
+
+    #[test]
+    fn test_allocator_reuses_slots_with_new_generation() {
+        let mut allocator = HandleAllocator::<Texture>::new();
+        let first = allocator.allocate();
+        let second = allocator.allocate();
+        assert_ne!(first, second);
+
+        allocator.free(first).unwrap();
+        assert!(!allocator.is_alive(first));
+        assert!(matches!(
+            allocator.free(first),
+            Err(GpuError::StaleHandle(_))
+        ));
+
+        let reused = allocator.allocate();
+        assert_eq!(reused.index(), first.index());
+        assert_eq!(reused.generation(), first.generation() + 1);
+        assert!(allocator.is_alive(reused) && allocator.is_alive(second));
+        assert!(matches!(
+            allocator.check(TextureId::new(7, 0)),
+            Err(GpuError::UnknownHandle(_))
+        ));
+    }
+
+    #[test]
+    fn test_table_reports_stale_handles() {
+        let mut allocator = HandleAllocator::<Texture>::new();
+        let mut table = ResourceTable::<Texture, &str>::new();
+        let old = allocator.allocate();
+        table.insert(old, "old").unwrap();
+        assert!(matches!(
+            table.insert(old, "again"),
+            Err(GpuError::HandleInUse(_))
+        ));
+
+        assert_eq!(table.remove(old), Ok("old"));
+        allocator.free(old).unwrap();
+        let new = allocator.allocate();
+        table.insert(new, "new").unwrap();
+
+        // the old handle does not alias the resource in its slot
+        assert_eq!(table.get(new), Ok(&"new"));
+        let err = table.get(old).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "TextureId { index: 0, generation: 0 } was destroyed"
+        );
+        assert!(matches!(
+            table.insert(old, "old"),
+            Err(GpuError::StaleHandle(_))
+        ));
+        assert!(matches!(
+            table.get(TextureId::new(3, 0)),
+            Err(GpuError::UnknownHandle(_))
+        ));
+    }
+
+    #[test]
+    fn test_table_pair() {
+        let mut table = ResourceTable::<Texture, u32>::new();
+        let (a, b) = (TextureId::new(0, 0), TextureId::new(2, 0));
+        table.insert(a, 1).unwrap();
+        table.insert(b, 2).unwrap();
+
+        let (second, first) = table.get_pair_mut(b, a).unwrap();
+        *first += *second;
+        assert_eq!(table.get(a), Ok(&3));
+        assert!(matches!(
+            table.get_pair_mut(a, a),
+            Err(GpuError::InvalidResource(_))
+        ));
+    }
+
+    #[test]
+    fn test_table_many() {
+        let mut table = ResourceTable::<Texture, u32>::new();
+        let ids = [0, 3, 1].map(|index| TextureId::new(index, 0));
+        for (value, &id) in ids.iter().enumerate() {
+            table.insert(id, value as u32).unwrap();
+        }
+
+        let values = table.get_many_mut(&[ids[2], ids[0]]).unwrap();
+        assert_eq!(values.iter().map(|v| **v).collect::<Vec<u32>>(), vec![2, 0]);
+        assert!(matches!(
+            table.get_many_mut(&[ids[1], ids[2], ids[1]]),
+            Err(GpuError::InvalidResource(_))
+        ));
+        assert!(matches!(
+            table.get_many_mut(&[ids[0], TextureId::new(2, 0)]),
+            Err(GpuError::UnknownHandle(_))
+        ));
+    }
+}
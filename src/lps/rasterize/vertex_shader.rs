@@ -1,35 +1,45 @@
 use crate::lps::common::math::mat4x4::Mat4x4;
 use crate::lps::common::math::vec4::Vec4;
-use std::{any::Any, sync::Arc};
+use crate::lps::rasterize::constant_buffer::{
+    ConstantBuffer, ConstantBufferBinding, ConstantBuffers,
+};
 
 use super::{pipeline::VertexShader, vt_input::VertexShaderInput, vt_output::VertexShaderOutput};
 
+/// The matrices `CustomVertexShader` reads from constant buffer 0.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Transforms {
+    pub model: Mat4x4,
+    pub view: Mat4x4,
+    pub proj: Mat4x4,
+}
+
+impl Transforms {
+    pub fn new(model: Mat4x4, view: Mat4x4, proj: Mat4x4) -> Self {
+        Transforms { model, view, proj }
+    }
+}
+
+impl ConstantBuffer for Transforms {}
+
 pub struct CustomVertexShader {
-    model_matrix: Option<Mat4x4>,
-    view_matrix: Option<Mat4x4>,
-    proj_matrix: Option<Mat4x4>,
+    transforms: Option<Transforms>,
 }
 
 impl CustomVertexShader {
     pub fn new() -> CustomVertexShader {
-        CustomVertexShader {
-            model_matrix: None,
-            view_matrix: None,
-            proj_matrix: None,
-        }
+        CustomVertexShader { transforms: None }
     }
 }
 
 impl VertexShader<VertexShaderInput, VertexShaderOutput> for CustomVertexShader {
     fn handle(&self, vertex: &VertexShaderInput) -> VertexShaderOutput {
-        // todo: handle None condition
-        let model_matrix = self.model_matrix.as_ref().unwrap();
-        let view_matrix = self.view_matrix.as_ref().unwrap();
-        let proj_matrix = self.proj_matrix.as_ref().unwrap();
+        let transforms = self.transforms.as_ref().unwrap();
 
-        let world_pos = *model_matrix * vertex.position;
-        let vw_pos = *view_matrix * world_pos;
-        let window_pos = *proj_matrix * vw_pos;
+        let world_pos = transforms.model * vertex.position;
+        let vw_pos = transforms.view * world_pos;
+        let window_pos = transforms.proj * vw_pos;
         let color = Vec4::new(vertex.color.x, vertex.color.y, vertex.color.z, 1.0);
         let texcoord = vertex.texcoord;
         let normal = vertex.normal;
@@ -37,30 +47,12 @@ impl VertexShader<VertexShaderInput, VertexShaderOutput> for CustomVertexShader
         VertexShaderOutput::new(world_pos, window_pos, color, texcoord, normal)
     }
 
-    fn init_constant_buffer(&mut self, buffer: &Vec<Option<Arc<dyn Any + Send>>>) {
-        self.model_matrix = Some(
-            buffer[0]
-                .as_ref()
-                .unwrap()
-                .downcast_ref::<Mat4x4>()
-                .unwrap()
-                .clone(),
-        );
-        self.view_matrix = Some(
-            buffer[1]
-                .as_ref()
-                .unwrap()
-                .downcast_ref::<Mat4x4>()
-                .unwrap()
-                .clone(),
-        );
-        self.proj_matrix = Some(
-            buffer[2]
-                .as_ref()
-                .unwrap()
-                .downcast_ref::<Mat4x4>()
-                .unwrap()
-                .clone(),
-        );
+    fn constant_buffer_layout(&self) -> Vec<ConstantBufferBinding> {
+        vec![ConstantBufferBinding::required::<Transforms>(0)]
+    }
+
+    fn init_constant_buffer(&mut self, buffer: &ConstantBuffers) {
+        // the Gpu checked the slot against the layout before the draw
+        self.transforms = buffer.get::<Transforms>(0).copied();
     }
 }
@@ -0,0 +1,44 @@
+use crate::lps::common::math::mat4x4::Mat4x4;
+
+/// The part of the render target normalized device coordinates are mapped to, and the
+/// depth range clip space z is mapped to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub min_depth: f32,
+    pub max_depth: f32,
+}
+
+impl Viewport {
+    /// A viewport with the full 0..1 depth range.
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    }
+
+    pub fn with_depth_range(mut self, min_depth: f32, max_depth: f32) -> Self {
+        self.min_depth = min_depth;
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn mat(&self) -> Mat4x4 {
+        Mat4x4::viewport_mat(
+            self.x,
+            self.y,
+            self.width,
+            self.height,
+            self.min_depth,
+            self.max_depth,
+        )
+    }
+}
@@ -1,14 +1,107 @@
 use crate::lps::common::math::{vec2::Vec2, vec3::Vec3, vec4::Vec4};
+pub use soft_renderer_derive::Varyings;
 
+/// A value that can be blended between two vertices.
+pub trait Lerp: Copy {
+    fn lerp(v1: Self, v2: Self, factor: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(v1: f32, v2: f32, factor: f32) -> f32 {
+        v1 + (v2 - v1) * factor
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(v1: Vec2, v2: Vec2, factor: f32) -> Vec2 {
+        Vec2::lerp(v1, v2, factor)
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(v1: Vec3, v2: Vec3, factor: f32) -> Vec3 {
+        Vec3::lerp(v1, v2, factor)
+    }
+}
+
+impl Lerp for Vec4 {
+    fn lerp(v1: Vec4, v2: Vec4, factor: f32) -> Vec4 {
+        Vec4::lerp(v1, v2, factor)
+    }
+}
+
+/// How far to blend from one vertex to the next. Smooth varyings use the
+/// perspective-correct factor, `noperspective` ones and the position the one in screen space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LerpFactor {
+    pub perspective: f32,
+    pub linear: f32,
+}
+
+impl LerpFactor {
+    pub fn new(perspective: f32, linear: f32) -> Self {
+        LerpFactor {
+            perspective,
+            linear,
+        }
+    }
+}
+
+/// The barycentric weights of a fragment, perspective-correct and in screen space. Each
+/// sums up to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BarycentricWeights {
+    pub perspective: [f32; 3],
+    pub linear: [f32; 3],
+}
+
+impl BarycentricWeights {
+    pub fn new(perspective: [f32; 3], linear: [f32; 3]) -> Self {
+        BarycentricWeights {
+            perspective,
+            linear,
+        }
+    }
+}
+
+/// Usually derived with `#[derive(Varyings)]`.
 pub trait VertexShaderOutputPositionAndLerp {
+    /// Clip space position out of the vertex shader. Once the Gpu has applied the viewport
+    /// transform, x, y, z are in screen space and w holds 1/w of the clip space position.
     fn position_as_mut(&mut self) -> &mut Vec4;
     fn position(&self) -> &Vec4;
-    fn lerp(v1: &Self, v2: &Self, factor: f32) -> Self;
+
+    /// Blends two vertices, flat varyings are taken from `v1`.
+    fn lerp(v1: &Self, v2: &Self, factor: LerpFactor) -> Self;
+
+    /// Overwrites the flat varyings with the ones of the provoking vertex of a primitive,
+    /// before the primitive is clipped and rasterized.
+    fn copy_flat(&mut self, _provoking: &Self) {}
+
+    /// Blends three vertices, flat varyings are taken from `v0`.
+    fn lerp_barycentric(v0: &Self, v1: &Self, v2: &Self, weights: &BarycentricWeights) -> Self
+    where
+        Self: Sized,
+    {
+        let split = |w: &[f32; 3]| {
+            let w01 = w[0] + w[1];
+            if w01.abs() < f32::EPSILON {
+                0.0
+            } else {
+                w[1] / w01
+            }
+        };
+        let factor = LerpFactor::new(split(&weights.perspective), split(&weights.linear));
+        let v01 = Self::lerp(v0, v1, factor);
+        let factor = LerpFactor::new(weights.perspective[2], weights.linear[2]);
+        Self::lerp(&v01, v2, factor)
+    }
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, Varyings)]
 pub struct VertexShaderOutput {
     pub world_pos: Vec4,
+    #[position]
     pub window_pos: Vec4,
     pub color: Vec4,
     pub texcoord: Vec2,
@@ -32,23 +125,3 @@ impl VertexShaderOutput {
         }
     }
 }
-
-impl VertexShaderOutputPositionAndLerp for VertexShaderOutput {
-    fn position_as_mut(&mut self) -> &mut Vec4 {
-        &mut self.window_pos
-    }
-
-    fn position(&self) -> &Vec4 {
-        &self.window_pos
-    }
-
-    fn lerp(v1: &VertexShaderOutput, v2: &VertexShaderOutput, factor: f32) -> VertexShaderOutput {
-        VertexShaderOutput::new(
-            Vec4::lerp(v1.world_pos, v2.world_pos, factor),
-            Vec4::lerp(v1.window_pos, v2.window_pos, factor),
-            Vec4::lerp(v1.color, v2.color, factor),
-            Vec2::lerp(v1.texcoord, v2.texcoord, factor),
-            Vec3::lerp(v1.normal, v2.normal, factor),
-        )
-    }
-}
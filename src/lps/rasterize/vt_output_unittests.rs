@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::lps::common::math::vec2::Vec2;
+    use crate::lps::common::math::vec4::Vec4;
+    use crate::lps::rasterize::vt_output::{
+        BarycentricWeights, LerpFactor, Varyings, VertexShaderOutputPositionAndLerp,
+    };
+
+    #[derive(Clone, Copy, Varyings)]
+    struct QualifiedVaryings {
+        #[position]
+        position: Vec4,
+        smooth_default: f32,
+        #[smooth]
+        smooth: f32,
+        #[noperspective]
+        linear: Vec2,
+        #[flat]
+        id: u32,
+    }
+
+    // the renderer under another path, as a crate depending on it would see it
+    mod renderer {
+        pub(super) use crate::lps;
+    }
+
+    #[derive(Clone, Copy, Varyings)]
+    #[varyings(crate = renderer)]
+    struct TupleVaryings(f32, #[position] Vec4);
+
+    fn create_varyings(value: f32, id: u32) -> QualifiedVaryings {
+        QualifiedVaryings {
+            position: Vec4::new(value, 0.0, 0.0, 1.0),
+            smooth_default: value,
+            smooth: value,
+            linear: Vec2::new(value, value),
+            id,
+        }
+    }
+
+    #[test]
+    fn test_derived_lerp_follows_the_qualifiers() {
+        let v1 = create_varyings(0.0, 1);
+        let v2 = create_varyings(1.0, 2);
+
+        let v = QualifiedVaryings::lerp(&v1, &v2, LerpFactor::new(0.25, 0.75));
+        assert_eq!(v.smooth_default, 0.25);
+        assert_eq!(v.smooth, 0.25);
+        assert_eq!(v.linear.x, 0.75);
+        assert_eq!(v.position().x, 0.75);
+        assert_eq!(v.id, 1);
+
+        let mut v = QualifiedVaryings::lerp(&v2, &v1, LerpFactor::new(0.5, 0.5));
+        assert_eq!(v.id, 2);
+        v.copy_flat(&v1);
+        assert_eq!(v.id, 1);
+        assert_eq!(v.smooth, 0.5);
+        v.copy_flat(&v2);
+        assert_eq!(v.id, 2);
+        v.position_as_mut().y = 3.0;
+        assert_eq!(v.position.y, 3.0);
+    }
+
+    #[test]
+    fn test_derived_lerp_barycentric() {
+        let v0 = create_varyings(0.0, 7);
+        let v1 = create_varyings(1.0, 8);
+        let v2 = create_varyings(2.0, 9);
+
+        let weights = BarycentricWeights::new([0.5, 0.5, 0.0], [0.0, 0.0, 1.0]);
+        let v = QualifiedVaryings::lerp_barycentric(&v0, &v1, &v2, &weights);
+        assert_eq!(v.smooth, 0.5);
+        assert_eq!(v.linear.y, 2.0);
+        assert_eq!(v.id, 7);
+
+        let t0 = TupleVaryings(0.0, Vec4::new(0.0, 0.0, 0.0, 1.0));
+        let t1 = TupleVaryings(4.0, Vec4::new(4.0, 0.0, 0.0, 1.0));
+        let t = TupleVaryings::lerp(&t0, &t1, LerpFactor::new(0.5, 0.25));
+        assert_eq!(t.0, 2.0);
+        assert_eq!(t.position().x, 1.0);
+    }
+}
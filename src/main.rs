@@ -6,16 +6,18 @@ use crate::lps::common::math::vec3::Vec3;
 use crate::lps::common::math::vec4::Vec4;
 use crate::lps::common::mesh::Mesh;
 use crate::lps::common::render_window::RenderWindow;
+use crate::lps::common::sampler::{AddressMode, FilterMode, Sampler};
 use crate::lps::common::texture::Texture;
 use lps::core::{bus::Bus, cpu::Cpu, gpu::Gpu};
+use lps::rasterize::pipeline_state::PipelineState;
 use lps::rasterize::pixel_shader::CustomPixelShader;
-use lps::rasterize::vertex_shader::CustomVertexShader;
-use std::ops::DerefMut;
+use lps::rasterize::rasterizer_state::{CullMode, FrontFace, RasterizerState};
+use lps::rasterize::render_cmds::clear::ClearFlags;
+use lps::rasterize::vertex_shader::{CustomVertexShader, Transforms};
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 
 use crate::lps::core::unit::Unit;
-use crate::lps::rasterize::render_target::RenderTarget;
 use crate::lps::rasterize::vt_input::VertexShaderInput;
 use crate::lps::rasterize::vt_output::VertexShaderOutput;
 
@@ -161,41 +163,55 @@ fn do_render(cpu: &mut Cpu, gpu_exit_mutex: Arc<Mutex<bool>>) {
     window.init();
 
     let mesh = create_box(&Vec3::new(0.0, 0.0, 0.0), 0.5);
-    let render_target = Arc::new(Mutex::new(RenderTarget::new(800, 600)));
-    let texture = Arc::new(Mutex::new(Texture::load("./data/wall.jpg")));
+    let render_target = cpu.create_render_target(800, 600);
+    let msaa_target = cpu.create_multisampled_render_target(800, 600, 4);
+    let texture = cpu.create_texture(Texture::load("./data/wall.jpg"));
 
     let mut angle = 0.0f32;
     let axis = Vec3::new(1.0, 1.0, 0.0).normal();
 
-    cpu.bind_constant_buffer_mat4x4(
-        1,
-        Mat4x4::view_mat(
-            &Vec3::new(0.0, 0.0, 5.0),
-            &Vec3::new(0.0, 0.0, -1.0),
-            &Vec3::new(1.0, 0.0, 0.0),
-            &Vec3::new(0.0, 1.0, 0.0),
-        ),
-    ); // view matrix
-    cpu.bind_constant_buffer_mat4x4(
-        2,
-        Mat4x4::perspective_mat(60.0f32.to_radians(), 800.0 / 600.0, 0.3, 100.0),
-    ); // proj matrix
-
-    cpu.bind_constant_buffer_texture(3, Arc::clone(&texture));
-
-    cpu.bind_render_target(Arc::clone(&render_target));
+    let view = Mat4x4::view_mat(
+        &Vec3::new(0.0, 0.0, 5.0),
+        &Vec3::new(0.0, 0.0, -1.0),
+        &Vec3::new(1.0, 0.0, 0.0),
+        &Vec3::new(0.0, 1.0, 0.0),
+    );
+    let proj = Mat4x4::perspective_mat(60.0f32.to_radians(), 800.0 / 600.0, 0.3, 100.0);
+
+    cpu.bind_texture(3, texture);
+    cpu.bind_constant_buffer(4, Sampler::new(AddressMode::Wrap, FilterMode::Trilinear));
+
+    cpu.bind_render_target(msaa_target);
+    let mesh = cpu.create_mesh_buffers(&mesh);
     cpu.bind_mesh(&mesh);
+    let pipeline_state = cpu.create_pipeline_state(
+        PipelineState::<VertexShaderInput, VertexShaderOutput>::new(
+            Box::new(CustomVertexShader::new()),
+            Box::new(CustomPixelShader::new()),
+        )
+        .with_rasterizer_state(RasterizerState::new(
+            CullMode::Back,
+            FrontFace::CounterClockwise,
+        )),
+    );
+    cpu.bind_pipeline(pipeline_state);
 
     loop {
         let rotate = Mat4x4::rotate_axis_mat(angle.to_radians(), axis.clone());
-        cpu.bind_constant_buffer_mat4x4(0, rotate); // model matrix
-        cpu.clear(Vec4::new(0.0, 0.0, 0.0, 1.0));
+        cpu.bind_constant_buffer(0, Transforms::new(rotate, view, proj));
+        cpu.clear(
+            ClearFlags::COLOR | ClearFlags::DEPTH | ClearFlags::STENCIL,
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+            1.0,
+            0,
+        );
         cpu.draw(true);
+        cpu.resolve(render_target);
         cpu.swap();
 
-        let mut unwrap = render_target.lock().unwrap();
+        let frame = cpu.read_render_target(render_target).unwrap();
 
-        let exit = window.update(unwrap.deref_mut());
+        let exit = window.update(&frame);
 
         angle += 360.0 / 20.0;
         angle %= 360.0;
@@ -218,7 +234,7 @@ fn main() {
 
     let fn_ptr = do_render;
     let mut cpu = Cpu::new(&bus, &exit_condvar_info, &render_complete_condvar_info, fn_ptr, Arc::clone(&gpu_exit_mutex));
-    let mut gpu = Gpu::<VertexShaderInput, VertexShaderOutput>::new(
+    let mut gpu = Gpu::new(
         &bus,
         &exit_condvar_info,
         &render_complete_condvar_info,
@@ -233,8 +249,6 @@ fn main() {
 
         let t2 = scope.spawn(|| {
             gpu.init();
-            gpu.bind_vertex_shader(Box::new(CustomVertexShader::new()));
-            gpu.bind_pixel_shader(Box::new(CustomPixelShader::new()));
             gpu.start();
         });
 